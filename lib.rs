@@ -2,6 +2,11 @@
 
 #[ink::contract]
 mod mock_dai {
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::hash::Blake2x256;
+    use ink::env::CallFlags;
+    use ink::prelude::string::String;
+    use ink::prelude::vec::Vec;
     use ink::storage::Mapping;
 
     /// Create storage for the mockDai ERC20 token contract
@@ -13,6 +18,237 @@ mod mock_dai {
         balances: Mapping<AccountId, Balance>, // mapping of an account (address) to a balance
         /// mapping of all token amount allowances for this token
         allowances: Mapping<(AccountId, AccountId), Balance>,
+        /// the privileged account allowed to call owner-only messages, set to the deployer
+        owner: AccountId,
+        /// fee charged on every transfer in basis points, 0 disables it
+        transfer_fee_bps: u16,
+        /// recipients of the transfer fee with their share in basis points, summing to `transfer_fee_bps`
+        fee_splits: Vec<(AccountId, u16)>,
+        /// id of the latest balance snapshot, 0 while none has been taken
+        snapshot_id: u32,
+        /// balances recorded lazily the first time an account changes after a snapshot
+        account_snapshots: Mapping<AccountId, Checkpoints>,
+        /// total supply recorded lazily the first time it changes after a snapshot
+        total_supply_snapshots: Checkpoints,
+        /// dividend funded for each epoch, keyed by the snapshot id taken when it was distributed
+        dividends: Mapping<u32, Balance>,
+        /// epochs an account has already claimed its dividend for
+        dividends_claimed: Mapping<(AccountId, u32), ()>,
+        /// share of every transfer burned in basis points, 0 disables it
+        burn_fee_bps: u16,
+        /// description of the error the latest mutating message failed with, if it failed
+        last_error_reason: Option<String>,
+        /// when on, registered receiver contracts have to acknowledge every incoming transfer
+        requires_ack: bool,
+        /// contracts that opted in to acknowledge their incoming transfers
+        ack_receivers: Mapping<AccountId, ()>,
+        /// receivers currently inside their `on_tokens_received` hook, kept in a mapping so the reentrant acknowledgement sees it
+        pending_acks: Mapping<AccountId, ()>,
+        /// permit nonce per owner, bumped by every signature consumed
+        nonces: Mapping<AccountId, u64>,
+        /// spends left on a limited-use allowance, which is revoked by the last of them. Absent means unlimited
+        allowance_uses: Mapping<(AccountId, AccountId), u32>,
+        /// highest allowance an owner can grant, `Balance::MAX` leaves it uncapped
+        max_allowance: Balance,
+        /// protocol treasury whose balance doesn't count as circulating
+        treasury: Option<AccountId>,
+        /// tokens each account has staked, held by the contract outside `balances`
+        staked: Mapping<AccountId, Balance>,
+        /// running total of `staked` so circulating supply needs no enumeration
+        total_staked: Balance,
+        /// tokens each account has locked and the block they unlock at
+        locks: Mapping<AccountId, (Balance, BlockNumber)>,
+        /// running total of `locks` so circulating supply needs no enumeration
+        total_locked: Balance,
+        /// when off the token is soulbound, only minting and burning still move balances
+        transferable: bool,
+        /// number of decimals the balances are denominated in
+        decimals: u8,
+        /// every redenomination so far as its factor and whether it multiplied, applied lazily to stored balances
+        redenominations: Vec<(Balance, bool)>,
+        /// number of redenominations an account's stored balance already reflects
+        balance_epochs: Mapping<AccountId, u32>,
+        /// block of the latest subscription pull per `(owner, merchant)`
+        last_pulls: Mapping<(AccountId, AccountId), BlockNumber>,
+        /// set once by `initialize` when the owner has finished setting up the deployment
+        initialized: bool,
+        /// accounts that neither pay nor trigger the transfer and burn fees, such as a DEX pair or the treasury
+        fee_exempt: Mapping<AccountId, ()>,
+        /// trusted bridge allowed to mint and burn the canonical bridged supply
+        bridge: Option<AccountId>,
+        /// number of bridge mints and burns so far, stamped on their events
+        bridge_nonce: u64,
+        /// source-chain nonces of the bridge messages already minted, so none can be replayed
+        processed_nonces: Mapping<u128, ()>,
+        /// last `RECENT_TRANSFERS_CAP` transfers per account, oldest first
+        recent_transfers: Mapping<AccountId, TransferHistory>,
+        /// number of accounts holding a nonzero balance
+        holder_count: u32,
+        /// most distinct holders allowed, `u32::MAX` leaves it uncapped
+        max_holders: u32,
+        /// sum of the outstanding allowances each owner has granted, saturating at `Balance::MAX`
+        total_allowance_granted: Mapping<AccountId, Balance>,
+        /// whether the transfer fee is reflected to every holder instead of going to the fee splits
+        reflect_fees: bool,
+        /// global reflection index scaled by `REFLECTION_PRECISION`, grown by every reflected fee
+        reflection_index: Balance,
+        /// reflection index each balance was last written at, absent means `REFLECTION_PRECISION`
+        reflection_marks: Mapping<AccountId, Balance>,
+        /// sum of the balances, i.e. the supply the reflected fees are shared among
+        total_held: Balance,
+        /// smallest nonzero balance a transfer may leave its sender with
+        min_balance: Balance,
+        /// most tokens that may ever be in circulation, 0 for no cap
+        supply_cap: Balance,
+        /// smallest amount a single transfer may move, 0 for no minimum
+        min_transfer: Balance,
+        /// largest amount a single transfer may move, 0 for no maximum
+        max_transfer: Balance,
+        /// each owner's granted allowance total as it stood when a block changing it began, one
+        /// `(block, total)` cell per checkpoint index so the history never sits in one value
+        exposure_checkpoints: Mapping<(AccountId, u32), ExposureCheckpoint>,
+        /// how many exposure checkpoints each owner has
+        exposure_checkpoint_count: Mapping<AccountId, u32>,
+        /// one-way switch permanently disabling every mint path
+        minting_renounced: bool,
+        /// external policy contract every transfer is checked against, if any
+        policy: Option<AccountId>,
+        /// holders per `HOLDER_BUCKETS` range, indexed like the thresholds
+        holder_buckets: Vec<u32>,
+        /// cut of every owner mint minted on top to the treasury, in basis points
+        treasury_mint_bps: u16,
+        /// most an account may send per limit window, `Balance::MAX` leaves it uncapped
+        daily_limit: Balance,
+        /// length of the transfer limit window in blocks
+        limit_window: BlockNumber,
+        /// volume each account sent in the limit window it last sent in, keyed by that window's index
+        sent_volume: Mapping<AccountId, (BlockNumber, Balance)>,
+        /// tokens minted per unit of native currency swapped in, and burned per unit swapped out
+        native_peg: Balance,
+        /// tokens sent to the zero address, held outside the balances until the owner recovers them
+        burned_accidentally: Balance,
+        /// accounts the owner granted minting next to itself
+        minters: Mapping<AccountId, ()>,
+        /// block the launch cooldown starts at
+        launch_block: BlockNumber,
+        /// blocks after `launch_block` during which an account may send once per block, 0 disables it
+        launch_cooldown: BlockNumber,
+        /// block each account last sent in during the launch cooldown
+        last_launch_transfer: Mapping<AccountId, BlockNumber>,
+        /// whether transfers are halted, set by the owner or by the circuit breaker
+        transfers_paused: bool,
+        /// outflow per breaker window that trips the circuit breaker, `Balance::MAX` disables it
+        breaker_threshold: Balance,
+        /// length of the circuit breaker window in blocks
+        breaker_window: BlockNumber,
+        /// volume transferred in the breaker window it was last counted in, keyed by that window's index
+        outflow: (BlockNumber, Balance),
+        /// whether the transfer and burn fees round up instead of down
+        round_fee_up: bool,
+        /// opaque owner-set label per account, for test harnesses
+        tags: Mapping<AccountId, Vec<u8>>,
+        /// accounts that ever held a balance, so `FirstReceipt` fires once per account
+        has_received: Mapping<AccountId, ()>,
+        /// discounted transfer fee rates as `(min_balance, fee_bps)` in increasing balance order
+        fee_tiers: Vec<(Balance, u16)>,
+        /// transfer fees accrued to the contract itself, held outside the balances until the owner withdraws them
+        collected_fees: Balance,
+        /// tokens each account parked in its numbered sub-accounts, held outside its liquid balance
+        sub_balances: Mapping<(AccountId, u32), Balance>,
+        /// sum of all sub-account balances
+        total_in_subs: Balance,
+        /// assets backing the vault shares
+        total_assets: Balance,
+        /// vault shares each account minted through `deposit` and hasn't redeemed, a part of its balance
+        vault_shares: Mapping<AccountId, Balance>,
+        /// sum of all vault shares, the total the vault prices against rather than the whole supply
+        total_vault_shares: Balance,
+        /// whether approvals are halted, independently of transfers
+        approvals_paused: bool,
+        /// recurring payment schedules by id
+        subscriptions: Mapping<u32, Subscription>,
+        /// id the next `create_subscription` hands out
+        next_subscription_id: u32,
+        /// per-spender allowance caps the owner set on sensitive spenders
+        spender_caps: Mapping<AccountId, Balance>,
+        /// balances still in the pre-upgrade layout, raw as of before any redenomination or reflection and moved into `balances` on their first write
+        legacy_balances: Mapping<AccountId, Balance>,
+        /// accounts whose legacy balance has been moved over
+        migrated: Mapping<AccountId, ()>,
+        /// number of spenders each owner currently has a non-zero allowance for
+        approval_counts: Mapping<AccountId, u32>,
+        /// most spenders an owner may have approved at once, `u32::MAX` disables it
+        max_approvals_per_owner: u32,
+        /// block minting becomes possible at, fixed once set and active from the start while unset
+        mint_activation_block: Option<BlockNumber>,
+        /// level an auto-topup allowance refills to after every spend, per `(owner, spender)`
+        allowance_refills: Mapping<(AccountId, AccountId), Balance>,
+        /// blocks a queued transfer waits before it can be finalized
+        transfer_delay: BlockNumber,
+        /// queued transfers by id
+        transfer_requests: Mapping<u32, TransferRequest>,
+        /// id the next `request_transfer` hands out
+        next_transfer_request_id: u32,
+        /// running total held back by queued transfers
+        total_queued: Balance,
+        /// contracts notified of each watched account's balance changes, at most `MAX_BALANCE_SUBSCRIBERS` per account
+        balance_subscribers: Mapping<AccountId, Vec<AccountId>>,
+        /// symbols of the extra mock tokens by id, token 0 being this deployment's own
+        token_symbols: Mapping<u32, String>,
+        /// id the next `register_token` hands out
+        next_token_id: u32,
+        /// balances of the extra mock tokens per `(token_id, account)`
+        token_balances: Mapping<(u32, AccountId), Balance>,
+        /// total supply of each extra mock token
+        token_supplies: Mapping<u32, Balance>,
+        /// mock USD price of one token, scaled by `PRICE_PRECISION`
+        price_usd: Balance,
+        /// block each vote-locked account may send again from
+        vote_locks: Mapping<AccountId, BlockNumber>,
+        /// external operation ids `mint_idempotent` already minted for
+        processed_ops: Mapping<u128, ()>,
+        /// share of an idle balance decaying away per block, in basis points
+        demurrage_bps: u16,
+        /// block the current demurrage rate took effect at, idle time before it is forgiven
+        demurrage_since: BlockNumber,
+        /// block each balance was last written at while demurrage is on
+        last_touch: Mapping<AccountId, BlockNumber>,
+        /// number of accounts that ever held tokens, never decremented
+        ever_holders: u32,
+        /// recipient class of each classified account, one of the `CLASS_` constants
+        classification: Mapping<AccountId, u8>,
+        /// transfer fee rate per recipient class, replacing the base fee for recipients of that class
+        class_fees: Mapping<u8, u16>,
+        /// only recipient a constrained allowance may be spent to, per `(owner, spender)`
+        allowance_recipients: Mapping<(AccountId, AccountId), AccountId>,
+        /// most transfers one sender may make in a single batch, `u32::MAX` disables it
+        max_transfers_per_tx: u32,
+        /// stabilizer parameters as `(reserve, reserve_ratio_bps, step_bps)`, `None` while it is off
+        stabilizer: Option<(Balance, u16, u16)>,
+        /// whether swapping back out to native currency is limited to `withdraw_whitelist`
+        withdraw_gated: bool,
+        /// accounts allowed to swap back out to native currency while `withdraw_gated` is on
+        withdraw_whitelist: Mapping<AccountId, ()>,
+        /// whether the callback-bearing messages refuse reentry, off only to exercise the reentrant path in tests
+        reentrancy_guard_enabled: bool,
+        /// set while a reentrant callback is in flight, checked by the reentrancy guard
+        entered: bool,
+        /// domain version mixed into every permit digest, bumping it voids every outstanding signature
+        domain_version: u32,
+        /// whether the transfer fee accrues to the backing reserve instead of going to the fee splits
+        fees_to_reserve: bool,
+        /// transfer fees held by the contract as the backing `redeem_from_reserve` pays out of
+        reserve: Balance,
+        /// vesting schedule of each account, dropped once it is fully claimed
+        vestings: Mapping<AccountId, Vesting>,
+        /// tokens still held in vesting schedules, vested or not
+        total_vesting: Balance,
+        /// dividend tokens escrowed by `distribute` and not claimed yet
+        total_dividends_unclaimed: Balance,
+        /// part of each epoch's dividend not claimed yet, removed once the owner sweeps it
+        dividends_unclaimed: Mapping<u32, Balance>,
+        /// `(account, subscriber)` pairs where `account` let `subscriber` watch its balance
+        approved_subscribers: Mapping<(AccountId, AccountId), ()>,
     }
 
     /// Transfer event to be fired when a token transfer occurs between users
@@ -34,6 +270,252 @@ mod mock_dai {
         amount: Balance,
     }
 
+    /// Burn event to be fired when tokens are destroyed, reducing the total supply
+    #[ink(event)]
+    pub struct Burn {
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance,
+    }
+
+    /// Snapshot event to be fired when the owner records the current balances under a new id
+    #[ink(event)]
+    pub struct Snapshot {
+        id: u32,
+    }
+
+    /// Fired when the owner funds a dividend for the holders of the snapshot `epoch`
+    #[ink(event)]
+    pub struct DividendDistributed {
+        #[ink(topic)]
+        epoch: u32,
+        amount: Balance,
+    }
+
+    /// Fired when a holder claims its share of the dividend of `epoch`
+    #[ink(event)]
+    pub struct DividendClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        epoch: u32,
+        amount: Balance,
+    }
+
+    /// Fired when an account moves tokens from its balance into staking
+    #[ink(event)]
+    pub struct Staked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Fired when an account moves tokens between its liquid balance (`None`) and its sub-accounts
+    #[ink(event)]
+    pub struct SubAccountMoved {
+        #[ink(topic)]
+        account: AccountId,
+        from_sub: Option<u32>,
+        to_sub: Option<u32>,
+        amount: Balance,
+    }
+
+    /// Fired when an account moves staked tokens back into its balance
+    #[ink(event)]
+    pub struct Unstaked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Fired when an account locks tokens until `until` (the block they can be released at)
+    #[ink(event)]
+    pub struct Locked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        until: BlockNumber,
+    }
+
+    /// Fired when an account's lock is released back into its balance
+    #[ink(event)]
+    pub struct Unlocked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Fired when the owner redenominates the token to `decimals`
+    #[ink(event)]
+    pub struct Redenominated {
+        decimals: u8,
+        factor: Balance,
+        multiply: bool,
+    }
+
+    /// Fired next to the `Transfer` when the bridge mints tokens arriving from another chain,
+    /// `src_nonce` being the nonce of the message on the source chain
+    #[ink(event)]
+    pub struct BridgeMint {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+        nonce: u64,
+        src_nonce: u128,
+    }
+
+    /// Fired next to the `Burn` when the bridge burns tokens leaving for another chain
+    #[ink(event)]
+    pub struct BridgeBurn {
+        #[ink(topic)]
+        from: AccountId,
+        amount: Balance,
+        nonce: u64,
+    }
+
+    /// Reflected event to be fired when a transfer fee is shared out among all holders
+    #[ink(event)]
+    pub struct Reflected {
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance,
+    }
+
+    /// ConfigChanged event to be fired by every owner setter of a contract-wide numeric or on/off
+    /// setting, the per-class fees and stabilizer parameters included, with `key` one of the
+    /// `ConfigKey` values, flags encoded as 0 or 1 and unset values as 0. Addresses, per-account
+    /// settings and the fee tiers fire `AddressChanged`, `AccountSettingChanged` and `FeeTiersChanged`
+    #[ink(event)]
+    pub struct ConfigChanged {
+        #[ink(topic)]
+        key: u8,
+        old_value: u128,
+        new_value: u128,
+    }
+
+    /// AddressChanged event to be fired when the owner sets the treasury, policy or bridge account,
+    /// with `key` their `ConfigKey`
+    #[ink(event)]
+    pub struct AddressChanged {
+        #[ink(topic)]
+        key: u8,
+        old_address: Option<AccountId>,
+        new_address: Option<AccountId>,
+    }
+
+    /// AccountSettingChanged event to be fired when the owner changes a setting of one account, with
+    /// `key` its `ConfigKey`, flags encoded as 0 or 1 and a removed spender cap as `Balance::MAX`
+    #[ink(event)]
+    pub struct AccountSettingChanged {
+        #[ink(topic)]
+        key: u8,
+        #[ink(topic)]
+        account: AccountId,
+        old_value: u128,
+        new_value: u128,
+    }
+
+    /// FeeTiersChanged event to be fired when the owner replaces the loyalty fee tiers
+    #[ink(event)]
+    pub struct FeeTiersChanged {
+        fee_tiers: Vec<(Balance, u16)>,
+    }
+
+    /// CircuitBreakerTripped event to be fired when the outflow in a window passes the breaker
+    /// threshold and transfers get paused
+    #[ink(event)]
+    pub struct CircuitBreakerTripped {
+        window: BlockNumber,
+        outflow: Balance,
+    }
+
+    /// FirstReceipt event to be fired the first time an account's balance goes from zero to positive
+    #[ink(event)]
+    pub struct FirstReceipt {
+        #[ink(topic)]
+        account: AccountId,
+        block: BlockNumber,
+    }
+
+    /// Fired when `from` queues a transfer to `to` that can be finalized from block `release`
+    #[ink(event)]
+    pub struct TransferRequested {
+        #[ink(topic)]
+        id: u32,
+        #[ink(topic)]
+        from: AccountId,
+        to: AccountId,
+        amount: Balance,
+        release: BlockNumber,
+    }
+
+    /// Fired when a queued transfer is cancelled and its amount returned to the sender
+    #[ink(event)]
+    pub struct TransferRequestCancelled {
+        #[ink(topic)]
+        id: u32,
+    }
+
+    /// Transfer event of an extra mock token, `from` is `None` for mints
+    #[ink(event)]
+    pub struct TokenTransfer {
+        #[ink(topic)]
+        token_id: u32,
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    /// Fired when `stabilize` nudges the total supply toward its target
+    #[ink(event)]
+    pub struct Stabilized {
+        minted: Balance,
+        burned: Balance,
+    }
+
+    /// Every enforced limit in one read, as returned by `limits`. A zero `cap`, `min_transfer` or
+    /// `max_transfer` means that limit isn't set
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Limits {
+        /// most tokens that may ever be in circulation
+        pub cap: Balance,
+        /// largest amount a single transfer may move
+        pub max_transfer: Balance,
+        /// smallest amount a single transfer may move
+        pub min_transfer: Balance,
+        /// most an account may send per limit window
+        pub daily_limit: Balance,
+        /// length of the transfer limit window in blocks
+        pub limit_window: BlockNumber,
+        /// least a non-empty balance may be left at
+        pub min_balance: Balance,
+        /// fee charged on every transfer in basis points
+        pub transfer_fee_bps: u16,
+        /// share of every transfer burned in basis points
+        pub burn_fee_bps: u16,
+        /// highest allowance an owner can grant
+        pub max_allowance: Balance,
+        /// most distinct holders allowed
+        pub max_holders: u32,
+        /// most transfers a single batch may carry
+        pub max_transfers_per_tx: u32,
+        /// blocks after launch during which each account may send once per block
+        pub launch_cooldown: BlockNumber,
+        /// blocks a requested transfer waits before it can be finalized
+        pub transfer_delay: BlockNumber,
+    }
+
+    /// Fired when the owner sweeps the unclaimed rest of the dividend of `epoch` back
+    #[ink(event)]
+    pub struct DividendSwept {
+        #[ink(topic)]
+        epoch: u32,
+        amount: Balance,
+    }
+
     /// Error specifications and handling
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -41,21 +523,979 @@ mod mock_dai {
         /// Trigger if the balance of the caller account cannot fulfill a request
         InsufficientBalance,
         InsufficientAllowance,
+        /// Trigger if an owner-only message is called by any other account
+        NotOwner,
+        /// Trigger if the fee splits don't add up to the configured fee
+        InvalidFeeConfig,
+        /// Trigger if a snapshot id hasn't been taken yet
+        InvalidSnapshot,
+        /// Trigger if no dividend was distributed for the requested epoch
+        DividendNotFound,
+        /// Trigger if the caller already claimed the dividend of the requested epoch
+        DividendAlreadyClaimed,
+        /// Trigger if an intermediate calculation doesn't fit into a `Balance`
+        Overflow,
+        /// Trigger if a fee is set above `MAX_FEE_BPS`
+        FeeTooHigh,
+        /// Trigger if a registered receiver contract didn't acknowledge an incoming transfer
+        ReceiverNotAcknowledged,
+        /// Trigger if `acknowledge_receipt` is called without a transfer waiting on the caller
+        NoPendingReceipt,
+        /// Trigger if a permit is submitted after its deadline
+        PermitExpired,
+        /// Trigger if a permit signature wasn't made by the owner over the expected digest
+        InvalidSignature,
+        /// Trigger if an approval is above the configured allowance cap
+        AllowanceTooHigh,
+        /// Trigger if locked tokens are released before their unlock block
+        StillLocked,
+        /// Trigger if a transfer is attempted while the token is soulbound
+        NonTransferable,
+        /// Trigger if the spender's callback after an approval reverts
+        CallbackFailed,
+        /// Trigger if a batch holds more than `MAX_BATCH` entries
+        BatchTooLarge,
+        /// Trigger if a transfer comes before its cooldown has passed
+        TransferCooldown,
+        /// Trigger if `initialize` is called a second time
+        AlreadyInitialized,
+        /// Trigger if a bridge-only message is called by any other account
+        NotBridge,
+        /// Trigger if a bridge message with an already processed source nonce is replayed
+        NonceAlreadyProcessed,
+        /// Trigger if a transfer or mint would add a holder beyond `max_holders`
+        HolderCapReached,
+        /// Trigger if a transfer would leave the sender with a nonzero balance below `min_balance`
+        BelowMinimum,
+        /// Trigger if a mint would take the total supply past the supply cap
+        SupplyCapExceeded,
+        /// Trigger if a transfer moves less than `min_transfer`
+        TransferTooSmall,
+        /// Trigger if a transfer moves more than `max_transfer`
+        TransferTooLarge,
+        /// Trigger if the owner sets a transfer minimum above the transfer maximum
+        InvalidTransferBounds,
+        /// Trigger if tokens are minted after minting was renounced
+        MintingRenounced,
+        /// Trigger if the policy contract rejects a transfer or can't be asked
+        PolicyRejected,
+        /// Trigger if a transfer would push the sender past `daily_limit` for the current window
+        DailyLimitExceeded,
+        /// Trigger if the owner sets a peg of zero or invalid stabilizer parameters
+        InvalidPeg,
+        /// Trigger if the contract holds too little native currency to pay a swap out
+        InsufficientReserve,
+        /// Trigger if the current allowance doesn't match the one `compare_and_approve` expected
+        AllowanceChanged,
+        /// Trigger if neither the owner nor a registered minter calls a mint message
+        NotMinter,
+        /// Trigger if a transfer is attempted while transfers are paused
+        Paused,
+        /// Trigger if an account tag is longer than `MAX_TAG_LEN` bytes
+        TagTooLong,
+        /// Trigger if an approval is attempted while approvals are paused
+        ApprovalsPaused,
+        /// Trigger if a subscription installment is executed before it falls due
+        NotDue,
+        /// Trigger if every installment of a subscription was already executed
+        SubscriptionEnded,
+        /// Trigger if no subscription exists under the given id
+        SubscriptionNotFound,
+        /// Trigger if an owner approves a new spender past `max_approvals_per_owner`
+        TooManyApprovals,
+        /// Trigger if a mint is attempted before `mint_activation_block`
+        MintingNotActive,
+        /// Trigger if the owner sets the mint activation block a second time
+        MintActivationFixed,
+        /// Trigger if no queued transfer exists under the given id
+        TransferRequestNotFound,
+        /// Trigger if anyone but the sender cancels a queued transfer
+        NotRequester,
+        /// Trigger if an account already has `MAX_BALANCE_SUBSCRIBERS` balance subscribers
+        TooManySubscribers,
+        /// Trigger if a token id was never registered
+        UnknownToken,
+        /// Trigger if a deadline-bound message runs after its deadline block
+        Expired,
+        /// Trigger if a constrained allowance is spent to any other recipient than its own
+        RecipientNotAllowed,
+        /// Trigger if a batch sends more transfers from one sender than `max_transfers_per_tx`
+        TooManyTransfers,
+        /// Trigger if a native withdrawal is gated and the caller isn't on the withdrawal whitelist
+        NotWhitelisted,
+        /// Trigger if a callback-bearing message is reentered while the reentrancy guard is on
+        Reentrant,
+        /// Trigger if a vesting schedule has no duration or a cliff past its end
+        InvalidSchedule,
+        /// Trigger if a vesting schedule is created for an account that still has one
+        VestingExists,
+        /// Trigger if the unclaimed rest of the dividend of the requested epoch was already swept
+        DividendSwept,
+        /// Trigger if a redenomination is attempted while tokens are still held in escrow
+        EscrowOutstanding,
+        /// Trigger if a contract subscribes to an account that didn't approve it as a subscriber
+        SubscriberNotApproved,
+        /// Trigger if an account redeems more vault shares than it deposited for
+        InsufficientVaultShares,
+    }
+
+    impl Error {
+        /// Human-readable description of the error for frontends
+        pub fn reason(&self) -> &'static str {
+            match self {
+                Error::InsufficientBalance => "balance too low for the requested amount",
+                Error::InsufficientAllowance => "allowance too low for the requested amount",
+                Error::NotOwner => "caller is not the owner",
+                Error::InvalidFeeConfig => "fee splits don't add up to the fee",
+                Error::InvalidSnapshot => "snapshot id hasn't been taken",
+                Error::DividendNotFound => "no dividend was distributed for this epoch",
+                Error::DividendAlreadyClaimed => "dividend already claimed for this epoch",
+                Error::Overflow => "calculation overflowed",
+                Error::FeeTooHigh => "fee is above the 10% cap",
+                Error::ReceiverNotAcknowledged => {
+                    "receiver contract didn't acknowledge the transfer"
+                }
+                Error::NoPendingReceipt => "no transfer is waiting for the caller to acknowledge",
+                Error::PermitExpired => "permit deadline has passed",
+                Error::InvalidSignature => "permit signature doesn't match the owner",
+                Error::AllowanceTooHigh => "allowance is above the configured cap",
+                Error::StillLocked => "tokens are still locked",
+                Error::NonTransferable => "token is non-transferable",
+                Error::CallbackFailed => "spender callback reverted",
+                Error::BatchTooLarge => "batch holds more than MAX_BATCH entries",
+                Error::TransferCooldown => "transfer is still cooling down",
+                Error::AlreadyInitialized => "contract is already initialized",
+                Error::NotBridge => "caller is not the bridge",
+                Error::NonceAlreadyProcessed => "bridge message was already processed",
+                Error::HolderCapReached => "holder cap reached",
+                Error::BelowMinimum => "balance below minimum",
+                Error::SupplyCapExceeded => "supply cap exceeded",
+                Error::TransferTooSmall => "transfer below minimum amount",
+                Error::TransferTooLarge => "transfer above maximum amount",
+                Error::InvalidTransferBounds => "transfer minimum above maximum",
+                Error::MintingRenounced => "minting renounced",
+                Error::PolicyRejected => "transfer rejected by policy",
+                Error::DailyLimitExceeded => "daily limit exceeded",
+                Error::InvalidPeg => "invalid peg",
+                Error::InsufficientReserve => "insufficient native reserve",
+                Error::AllowanceChanged => "allowance changed",
+                Error::NotMinter => "caller is not a minter",
+                Error::Paused => "transfers are paused",
+                Error::TagTooLong => "tag too long",
+                Error::ApprovalsPaused => "approvals are paused",
+                Error::NotDue => "installment is not due yet",
+                Error::SubscriptionEnded => "subscription has ended",
+                Error::SubscriptionNotFound => "no subscription with this id",
+                Error::TooManyApprovals => "too many approved spenders",
+                Error::MintingNotActive => "minting is not active yet",
+                Error::MintActivationFixed => "mint activation block is already set",
+                Error::TransferRequestNotFound => "no queued transfer with this id",
+                Error::NotRequester => "caller did not queue this transfer",
+                Error::TooManySubscribers => "too many balance subscribers",
+                Error::UnknownToken => "unknown token id",
+                Error::Expired => "deadline has passed",
+                Error::RecipientNotAllowed => "recipient not allowed for this allowance",
+                Error::TooManyTransfers => "too many transfers in one transaction",
+                Error::NotWhitelisted => "caller is not on the withdrawal whitelist",
+                Error::Reentrant => "callback-bearing message reentered",
+                Error::InvalidSchedule => "invalid vesting schedule",
+                Error::VestingExists => "account already has a vesting schedule",
+                Error::DividendSwept => "dividend of this epoch was swept",
+                Error::EscrowOutstanding => "tokens are still held in escrow",
+                Error::SubscriberNotApproved => "account did not approve this subscriber",
+                Error::InsufficientVaultShares => "not enough vault shares",
+            }
+        }
     }
 
     /// Token result type specification
     pub type Result<T> = core::result::Result<T, Error>;
 
+    /// Values recorded per snapshot id (or block number), in increasing order
+    pub type Checkpoints = Vec<(u32, Balance)>;
+
+    /// An owner's granted allowance total as a block changing it began, stored as `(block, total)`
+    pub type ExposureCheckpoint = (BlockNumber, Balance);
+
+    /// Version of this contract's code, bumped on every release
+    pub const CONTRACT_VERSION: u16 = 1;
+
+    /// Most entries a batch message accepts. Larger batches are rejected up front with
+    /// `Error::BatchTooLarge` instead of running out of gas halfway through
+    pub const MAX_BATCH: usize = 100;
+
+    /// Longest account tag in bytes
+    pub const MAX_TAG_LEN: usize = 32;
+
+    /// Transfers an account took part in as `(counterparty, amount, block)`, oldest first
+    pub type TransferHistory = Vec<(AccountId, Balance, BlockNumber)>;
+
+    /// A recurring payment as `(payer, recipient, amount, interval, first due block, installments
+    /// in total, installments executed)`
+    pub type Subscription = (
+        AccountId,
+        AccountId,
+        Balance,
+        BlockNumber,
+        BlockNumber,
+        u32,
+        u32,
+    );
+
+    /// A queued transfer as `(from, to, amount, release block)`
+    pub type TransferRequest = (AccountId, AccountId, Balance, BlockNumber);
+
+    /// A vesting schedule as `(total, start block, cliff in blocks, duration in blocks, claimed)`
+    pub type Vesting = (Balance, BlockNumber, BlockNumber, BlockNumber, Balance);
+
+    /// Transfers kept per account in `recent_transfers`
+    pub const RECENT_TRANSFERS_CAP: usize = 10;
+
+    /// Lower bounds of the balance ranges `holder_distribution` counts holders in, in the smallest
+    /// unit. Each range runs up to the next threshold, the last one is open-ended
+    pub const HOLDER_BUCKETS: [Balance; 10] = [
+        1,
+        1_000,
+        1_000_000,
+        1_000_000_000,
+        1_000_000_000_000,
+        1_000_000_000_000_000,
+        1_000_000_000_000_000_000,
+        1_000_000_000_000_000_000_000,
+        1_000_000_000_000_000_000_000_000,
+        1_000_000_000_000_000_000_000_000_000,
+    ];
+
+    /// Default transfer limit window, a day of 6 second blocks
+    pub const DEFAULT_LIMIT_WINDOW: BlockNumber = 14_400;
+
+    /// Stable keys of the settings reported by `ConfigChanged`, `AddressChanged` and
+    /// `AccountSettingChanged`, never renumbered
+    #[derive(Clone, Copy)]
+    pub enum ConfigKey {
+        TransferFee = 0,
+        BurnFee = 1,
+        MinBalance = 2,
+        DailyLimit = 3,
+        LimitWindow = 4,
+        ReflectFees = 5,
+        RequiresAck = 6,
+        MaxAllowance = 7,
+        TreasuryMintBps = 8,
+        MintingRenounced = 9,
+        NativePeg = 10,
+        Transferable = 11,
+        MaxHolders = 12,
+        LaunchBlock = 13,
+        LaunchCooldown = 14,
+        BreakerThreshold = 15,
+        BreakerWindow = 16,
+        Paused = 17,
+        RoundFeeUp = 18,
+        ApprovalsPaused = 19,
+        MaxApprovalsPerOwner = 20,
+        MintActivationBlock = 21,
+        TransferDelay = 22,
+        Price = 23,
+        DemurrageBps = 24,
+        MaxTransfersPerTx = 25,
+        WithdrawGated = 26,
+        ReentrancyGuard = 27,
+        DomainVersion = 28,
+        FeesToReserve = 29,
+        ClassFeeEoa = 30,
+        ClassFeeContract = 31,
+        ClassFeeExchange = 32,
+        StabilizerReserve = 33,
+        StabilizerRatio = 34,
+        StabilizerStep = 35,
+        Treasury = 36,
+        Policy = 37,
+        Bridge = 38,
+        Minter = 39,
+        FeeExempt = 40,
+        SpenderCap = 41,
+        Classification = 42,
+        SupplyCap = 43,
+        MinTransfer = 44,
+        MaxTransfer = 45,
+    }
+
+    /// Fixed-point scale of `price_usd`, a price of exactly 1 USD
+    pub const PRICE_PRECISION: Balance = 1_000_000_000_000_000_000;
+
+    /// Recipient class of an account never classified, paying the base transfer fee
+    pub const CLASS_UNCLASSIFIED: u8 = 0;
+
+    /// Recipient class of an externally owned account
+    pub const CLASS_EOA: u8 = 1;
+
+    /// Recipient class of a contract
+    pub const CLASS_CONTRACT: u8 = 2;
+
+    /// Recipient class of an exchange
+    pub const CLASS_EXCHANGE: u8 = 3;
+
+    #[cfg(test)]
+    thread_local! {
+        /// Whether `emit` skips events, per test thread
+        static SUPPRESS_EVENTS: core::cell::Cell<bool> = const { core::cell::Cell::new(false) };
+    }
+
+    /// Basis points making up 100%
+    pub const BPS_DENOMINATOR: u16 = 10_000;
+
+    /// Highest fee the owner can configure, 10%
+    pub const MAX_FEE_BPS: u16 = 1_000;
+
+    /// Fixed-point scale of the reflection index
+    pub const REFLECTION_PRECISION: Balance = 1_000_000_000_000;
+
+    /// Floors `amount * numerator / denominator`. A rate of at most one can't overflow, and a zero
+    /// denominator only comes with a zero rate, so both yield plain balances
+    fn portion(amount: Balance, numerator: u16, denominator: u16) -> Balance {
+        mul_div(amount, numerator.into(), denominator.into()).unwrap_or_default()
+    }
+
+    /// Ceils `amount * numerator / denominator`, see `portion`
+    fn portion_ceil(amount: Balance, numerator: u16, denominator: u16) -> Balance {
+        match mul_div_rem(amount, numerator.into(), denominator.into()) {
+            Some((floor, 0)) => floor,
+            Some((floor, _)) => floor + 1,
+            None => 0,
+        }
+    }
+
+    /// Floors `a * b / denom` through a full 256-bit intermediate product, so only a quotient that
+    /// doesn't fit a `Balance` (or a zero `denom`) is an `Overflow`
+    fn mul_div(a: Balance, b: Balance, denom: Balance) -> Result<Balance> {
+        mul_div_rem(a, b, denom)
+            .map(|(quotient, _)| quotient)
+            .ok_or(Error::Overflow)
+    }
+
+    /// Quotient and remainder of `a * b / denom` for `mul_div`, `None` when the quotient overflows
+    fn mul_div_rem(a: Balance, b: Balance, denom: Balance) -> Option<(Balance, Balance)> {
+        const LOW: Balance = u64::MAX as Balance;
+        // schoolbook 128×128 multiplication on 64-bit limbs into `high:low`
+        let (a_high, a_low) = (a >> 64, a & LOW);
+        let (b_high, b_low) = (b >> 64, b & LOW);
+        let (low_low, low_high) = (a_low * b_low, a_low * b_high);
+        let (high_low, high_high) = (a_high * b_low, a_high * b_high);
+        let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+        let low = (low_low & LOW) | (middle << 64);
+        let high = high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+
+        // the quotient fits 128 bits exactly when the high half is below the divisor
+        if high >= denom {
+            return None;
+        }
+        if high == 0 {
+            return Some((low / denom, low % denom));
+        }
+        // restoring long division of the low half's bits into the running remainder
+        let (mut quotient, mut remainder) = (0, high);
+        for bit in (0..128).rev() {
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | ((low >> bit) & 1);
+            quotient <<= 1;
+            if carry == 1 || remainder >= denom {
+                remainder = remainder.wrapping_sub(denom);
+                quotient |= 1;
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    /// Index of the `HOLDER_BUCKETS` range `balance` falls in, `None` for an empty balance
+    fn bucket_of(balance: Balance) -> Option<usize> {
+        HOLDER_BUCKETS
+            .partition_point(|threshold| *threshold <= balance)
+            .checked_sub(1)
+    }
+
+    /// The all-zero account standing in for the zero address
+    fn zero_account() -> AccountId {
+        AccountId::from([0; 32])
+    }
+
+    /// Every message's label with its selector, in declaration order. For tooling and tests checking
+    /// the selectors, it isn't part of the dispatch. New messages have to be listed here too, the
+    /// tests hold it against the generated metadata
+    pub const SELECTOR_TABLE: &[(&str, [u8; 4])] = &[
+        ("total_supply", ink::selector_bytes!("total_supply")),
+        ("balance_of", ink::selector_bytes!("balance_of")),
+        ("my_balance", ink::selector_bytes!("my_balance")),
+        (
+            "would_create_entry",
+            ink::selector_bytes!("would_create_entry"),
+        ),
+        ("is_migrated", ink::selector_bytes!("is_migrated")),
+        ("migrate_account", ink::selector_bytes!("migrate_account")),
+        ("transfer", ink::selector_bytes!("transfer")),
+        ("psp22_transfer", ink::selector_bytes!("PSP22::transfer")),
+        (
+            "transfer_with_burn",
+            ink::selector_bytes!("transfer_with_burn"),
+        ),
+        ("transfer_by", ink::selector_bytes!("transfer_by")),
+        ("approve", ink::selector_bytes!("approve")),
+        ("set_allowance", ink::selector_bytes!("set_allowance")),
+        ("safe_approve", ink::selector_bytes!("safe_approve")),
+        (
+            "migrate_allowance",
+            ink::selector_bytes!("migrate_allowance"),
+        ),
+        (
+            "compare_and_approve",
+            ink::selector_bytes!("compare_and_approve"),
+        ),
+        (
+            "transfer_and_approve",
+            ink::selector_bytes!("transfer_and_approve"),
+        ),
+        ("approve_n_uses", ink::selector_bytes!("approve_n_uses")),
+        (
+            "approve_to_recipient",
+            ink::selector_bytes!("approve_to_recipient"),
+        ),
+        (
+            "allowance_recipient",
+            ink::selector_bytes!("allowance_recipient"),
+        ),
+        ("approve_auto", ink::selector_bytes!("approve_auto")),
+        ("allowance_refill", ink::selector_bytes!("allowance_refill")),
+        ("sum_balances", ink::selector_bytes!("sum_balances")),
+        ("allowance_matrix", ink::selector_bytes!("allowance_matrix")),
+        (
+            "total_approved_by",
+            ink::selector_bytes!("total_approved_by"),
+        ),
+        (
+            "approval_exposure_at",
+            ink::selector_bytes!("approval_exposure_at"),
+        ),
+        ("allowance_uses", ink::selector_bytes!("allowance_uses")),
+        (
+            "increase_allowance",
+            ink::selector_bytes!("increase_allowance"),
+        ),
+        (
+            "decrease_allowance",
+            ink::selector_bytes!("decrease_allowance"),
+        ),
+        ("allowance", ink::selector_bytes!("allowance")),
+        ("transfer_from", ink::selector_bytes!("transfer_from")),
+        (
+            "simulate_transfer_from",
+            ink::selector_bytes!("simulate_transfer_from"),
+        ),
+        ("max_pullable", ink::selector_bytes!("max_pullable")),
+        (
+            "transfer_from_all",
+            ink::selector_bytes!("transfer_from_all"),
+        ),
+        (
+            "supports_interface",
+            ink::selector_bytes!("supports_interface"),
+        ),
+        ("owner", ink::selector_bytes!("owner")),
+        ("sweep_dust", ink::selector_bytes!("sweep_dust")),
+        ("transfer_fee_bps", ink::selector_bytes!("transfer_fee_bps")),
+        ("fee_splits", ink::selector_bytes!("fee_splits")),
+        ("set_transfer_fee", ink::selector_bytes!("set_transfer_fee")),
+        ("quote_transfer", ink::selector_bytes!("quote_transfer")),
+        ("fee_tiers", ink::selector_bytes!("fee_tiers")),
+        ("set_fee_tiers", ink::selector_bytes!("set_fee_tiers")),
+        (
+            "classification_of",
+            ink::selector_bytes!("classification_of"),
+        ),
+        (
+            "set_classification",
+            ink::selector_bytes!("set_classification"),
+        ),
+        ("class_fee", ink::selector_bytes!("class_fee")),
+        ("set_class_fee", ink::selector_bytes!("set_class_fee")),
+        ("round_fee_up", ink::selector_bytes!("round_fee_up")),
+        ("set_round_fee_up", ink::selector_bytes!("set_round_fee_up")),
+        ("burn_fee_bps", ink::selector_bytes!("burn_fee_bps")),
+        ("set_burn_fee", ink::selector_bytes!("set_burn_fee")),
+        ("min_balance", ink::selector_bytes!("min_balance")),
+        ("set_min_balance", ink::selector_bytes!("set_min_balance")),
+        ("supply_cap", ink::selector_bytes!("supply_cap")),
+        ("set_supply_cap", ink::selector_bytes!("set_supply_cap")),
+        ("transfer_bounds", ink::selector_bytes!("transfer_bounds")),
+        (
+            "set_transfer_bounds",
+            ink::selector_bytes!("set_transfer_bounds"),
+        ),
+        ("paused", ink::selector_bytes!("paused")),
+        ("pause", ink::selector_bytes!("pause")),
+        ("unpause", ink::selector_bytes!("unpause")),
+        (
+            "set_transfers_paused",
+            ink::selector_bytes!("set_transfers_paused"),
+        ),
+        ("approvals_paused", ink::selector_bytes!("approvals_paused")),
+        (
+            "set_approvals_paused",
+            ink::selector_bytes!("set_approvals_paused"),
+        ),
+        ("circuit_breaker", ink::selector_bytes!("circuit_breaker")),
+        (
+            "set_circuit_breaker",
+            ink::selector_bytes!("set_circuit_breaker"),
+        ),
+        ("launch", ink::selector_bytes!("launch")),
+        ("set_launch", ink::selector_bytes!("set_launch")),
+        ("daily_limit", ink::selector_bytes!("daily_limit")),
+        ("sent_in_window", ink::selector_bytes!("sent_in_window")),
+        ("set_daily_limit", ink::selector_bytes!("set_daily_limit")),
+        ("reflect_fees", ink::selector_bytes!("reflect_fees")),
+        ("reflection_index", ink::selector_bytes!("reflection_index")),
+        ("set_reflect_fees", ink::selector_bytes!("set_reflect_fees")),
+        ("snapshot", ink::selector_bytes!("snapshot")),
+        ("balance_at", ink::selector_bytes!("balance_at")),
+        ("total_supply_at", ink::selector_bytes!("total_supply_at")),
+        ("distribute", ink::selector_bytes!("distribute")),
+        ("dividend_of", ink::selector_bytes!("dividend_of")),
+        ("claim_dividend", ink::selector_bytes!("claim_dividend")),
+        (
+            "dividend_unclaimed",
+            ink::selector_bytes!("dividend_unclaimed"),
+        ),
+        ("sweep_dividend", ink::selector_bytes!("sweep_dividend")),
+        (
+            "transfer_receipt_hash",
+            ink::selector_bytes!("transfer_receipt_hash"),
+        ),
+        (
+            "last_error_reason",
+            ink::selector_bytes!("last_error_reason"),
+        ),
+        ("requires_ack", ink::selector_bytes!("requires_ack")),
+        ("set_requires_ack", ink::selector_bytes!("set_requires_ack")),
+        ("is_ack_receiver", ink::selector_bytes!("is_ack_receiver")),
+        (
+            "register_ack_receiver",
+            ink::selector_bytes!("register_ack_receiver"),
+        ),
+        (
+            "acknowledge_receipt",
+            ink::selector_bytes!("acknowledge_receipt"),
+        ),
+        ("nonces", ink::selector_bytes!("nonces")),
+        ("claim_leaf", ink::selector_bytes!("claim_leaf")),
+        ("nonces_of", ink::selector_bytes!("nonces_of")),
+        ("domain_separator", ink::selector_bytes!("domain_separator")),
+        ("domain_version", ink::selector_bytes!("domain_version")),
+        (
+            "bump_domain_version",
+            ink::selector_bytes!("bump_domain_version"),
+        ),
+        ("permit_digest", ink::selector_bytes!("permit_digest")),
+        ("permit", ink::selector_bytes!("permit")),
+        ("permit_vrs", ink::selector_bytes!("permit_vrs")),
+        (
+            "permit_single_use",
+            ink::selector_bytes!("permit_single_use"),
+        ),
+        ("force_revoke", ink::selector_bytes!("force_revoke")),
+        ("max_allowance", ink::selector_bytes!("max_allowance")),
+        (
+            "set_max_allowance",
+            ink::selector_bytes!("set_max_allowance"),
+        ),
+        ("spender_cap", ink::selector_bytes!("spender_cap")),
+        ("set_spender_cap", ink::selector_bytes!("set_spender_cap")),
+        ("treasury", ink::selector_bytes!("treasury")),
+        ("set_treasury", ink::selector_bytes!("set_treasury")),
+        ("policy", ink::selector_bytes!("policy")),
+        ("set_policy", ink::selector_bytes!("set_policy")),
+        ("tag_of", ink::selector_bytes!("tag_of")),
+        ("set_tag", ink::selector_bytes!("set_tag")),
+        ("staked_of", ink::selector_bytes!("staked_of")),
+        ("stake", ink::selector_bytes!("stake")),
+        ("unstake", ink::selector_bytes!("unstake")),
+        ("total_assets", ink::selector_bytes!("total_assets")),
+        ("vault_shares_of", ink::selector_bytes!("vault_shares_of")),
+        (
+            "total_vault_shares",
+            ink::selector_bytes!("total_vault_shares"),
+        ),
+        (
+            "convert_to_shares",
+            ink::selector_bytes!("convert_to_shares"),
+        ),
+        (
+            "convert_to_assets",
+            ink::selector_bytes!("convert_to_assets"),
+        ),
+        ("deposit", ink::selector_bytes!("deposit")),
+        ("redeem", ink::selector_bytes!("redeem")),
+        ("accrue_assets", ink::selector_bytes!("accrue_assets")),
+        ("sub_balance_of", ink::selector_bytes!("sub_balance_of")),
+        ("total_in_subs", ink::selector_bytes!("total_in_subs")),
+        ("deposit_to_sub", ink::selector_bytes!("deposit_to_sub")),
+        (
+            "withdraw_from_sub",
+            ink::selector_bytes!("withdraw_from_sub"),
+        ),
+        (
+            "move_between_subs",
+            ink::selector_bytes!("move_between_subs"),
+        ),
+        ("lock_of", ink::selector_bytes!("lock_of")),
+        ("lock", ink::selector_bytes!("lock")),
+        ("release_lock", ink::selector_bytes!("release_lock")),
+        ("vesting_of", ink::selector_bytes!("vesting_of")),
+        ("vested_amount", ink::selector_bytes!("vested_amount")),
+        ("create_vesting", ink::selector_bytes!("create_vesting")),
+        ("claim_vested", ink::selector_bytes!("claim_vested")),
+        (
+            "demurrage_bps_per_block",
+            ink::selector_bytes!("demurrage_bps_per_block"),
+        ),
+        (
+            "set_demurrage_bps_per_block",
+            ink::selector_bytes!("set_demurrage_bps_per_block"),
+        ),
+        ("vote_lock_of", ink::selector_bytes!("vote_lock_of")),
+        ("lock_for_vote", ink::selector_bytes!("lock_for_vote")),
+        ("price", ink::selector_bytes!("price")),
+        ("set_price", ink::selector_bytes!("set_price")),
+        ("value_of", ink::selector_bytes!("value_of")),
+        (
+            "balance_subscribers",
+            ink::selector_bytes!("balance_subscribers"),
+        ),
+        (
+            "is_subscriber_approved",
+            ink::selector_bytes!("is_subscriber_approved"),
+        ),
+        (
+            "approve_subscriber",
+            ink::selector_bytes!("approve_subscriber"),
+        ),
+        ("subscribe", ink::selector_bytes!("subscribe")),
+        ("unsubscribe", ink::selector_bytes!("unsubscribe")),
+        ("transfer_delay", ink::selector_bytes!("transfer_delay")),
+        (
+            "set_transfer_delay",
+            ink::selector_bytes!("set_transfer_delay"),
+        ),
+        ("transfer_request", ink::selector_bytes!("transfer_request")),
+        ("request_transfer", ink::selector_bytes!("request_transfer")),
+        (
+            "finalize_transfer",
+            ink::selector_bytes!("finalize_transfer"),
+        ),
+        ("cancel_transfer", ink::selector_bytes!("cancel_transfer")),
+        ("escrowed_supply", ink::selector_bytes!("escrowed_supply")),
+        ("liquid_supply", ink::selector_bytes!("liquid_supply")),
+        (
+            "circulating_supply",
+            ink::selector_bytes!("circulating_supply"),
+        ),
+        (
+            "check_conservation",
+            ink::selector_bytes!("check_conservation"),
+        ),
+        ("collected_fees", ink::selector_bytes!("collected_fees")),
+        ("withdraw_fees", ink::selector_bytes!("withdraw_fees")),
+        ("fees_to_reserve", ink::selector_bytes!("fees_to_reserve")),
+        (
+            "set_fees_to_reserve",
+            ink::selector_bytes!("set_fees_to_reserve"),
+        ),
+        ("reserve", ink::selector_bytes!("reserve")),
+        (
+            "redeem_from_reserve",
+            ink::selector_bytes!("redeem_from_reserve"),
+        ),
+        ("accidental_burns", ink::selector_bytes!("accidental_burns")),
+        (
+            "recover_accidental",
+            ink::selector_bytes!("recover_accidental"),
+        ),
+        ("mint", ink::selector_bytes!("mint")),
+        ("register_token", ink::selector_bytes!("register_token")),
+        ("token_symbol", ink::selector_bytes!("token_symbol")),
+        ("total_supply_of", ink::selector_bytes!("total_supply_of")),
+        ("balance_of_token", ink::selector_bytes!("balance_of_token")),
+        ("transfer_token", ink::selector_bytes!("transfer_token")),
+        ("mint_token", ink::selector_bytes!("mint_token")),
+        ("mint_idempotent", ink::selector_bytes!("mint_idempotent")),
+        ("can_mint", ink::selector_bytes!("can_mint")),
+        (
+            "mint_activation_block",
+            ink::selector_bytes!("mint_activation_block"),
+        ),
+        (
+            "set_mint_activation_block",
+            ink::selector_bytes!("set_mint_activation_block"),
+        ),
+        ("set_minter", ink::selector_bytes!("set_minter")),
+        (
+            "treasury_mint_bps",
+            ink::selector_bytes!("treasury_mint_bps"),
+        ),
+        (
+            "set_treasury_mint_bps",
+            ink::selector_bytes!("set_treasury_mint_bps"),
+        ),
+        (
+            "minting_renounced",
+            ink::selector_bytes!("minting_renounced"),
+        ),
+        ("renounce_minting", ink::selector_bytes!("renounce_minting")),
+        ("burn", ink::selector_bytes!("burn")),
+        ("native_peg", ink::selector_bytes!("native_peg")),
+        ("set_native_peg", ink::selector_bytes!("set_native_peg")),
+        ("stabilizer", ink::selector_bytes!("stabilizer")),
+        (
+            "stabilizer_target",
+            ink::selector_bytes!("stabilizer_target"),
+        ),
+        ("set_stabilizer", ink::selector_bytes!("set_stabilizer")),
+        ("stabilize", ink::selector_bytes!("stabilize")),
+        (
+            "swap_native_for_dai",
+            ink::selector_bytes!("swap_native_for_dai"),
+        ),
+        (
+            "swap_dai_for_native",
+            ink::selector_bytes!("swap_dai_for_native"),
+        ),
+        ("withdraw_gated", ink::selector_bytes!("withdraw_gated")),
+        (
+            "set_withdraw_gated",
+            ink::selector_bytes!("set_withdraw_gated"),
+        ),
+        (
+            "is_withdraw_whitelisted",
+            ink::selector_bytes!("is_withdraw_whitelisted"),
+        ),
+        (
+            "set_withdraw_whitelisted",
+            ink::selector_bytes!("set_withdraw_whitelisted"),
+        ),
+        (
+            "reentrancy_guard_enabled",
+            ink::selector_bytes!("reentrancy_guard_enabled"),
+        ),
+        (
+            "set_reentrancy_guard_enabled",
+            ink::selector_bytes!("set_reentrancy_guard_enabled"),
+        ),
+        ("transferable", ink::selector_bytes!("transferable")),
+        ("set_transferable", ink::selector_bytes!("set_transferable")),
+        ("approve_and_call", ink::selector_bytes!("approve_and_call")),
+        (
+            "approve_and_call_with_selector",
+            ink::selector_bytes!("approve_and_call_with_selector"),
+        ),
+        ("batch_transfer", ink::selector_bytes!("batch_transfer")),
+        (
+            "max_transfers_per_tx",
+            ink::selector_bytes!("max_transfers_per_tx"),
+        ),
+        (
+            "set_max_transfers_per_tx",
+            ink::selector_bytes!("set_max_transfers_per_tx"),
+        ),
+        ("batch_approve", ink::selector_bytes!("batch_approve")),
+        ("mint_batch", ink::selector_bytes!("mint_batch")),
+        ("name", ink::selector_bytes!("name")),
+        ("symbol", ink::selector_bytes!("symbol")),
+        ("decimals", ink::selector_bytes!("decimals")),
+        ("redenominate", ink::selector_bytes!("redenominate")),
+        ("last_pull_block", ink::selector_bytes!("last_pull_block")),
+        (
+            "pull_subscription",
+            ink::selector_bytes!("pull_subscription"),
+        ),
+        ("subscription", ink::selector_bytes!("subscription")),
+        (
+            "create_subscription",
+            ink::selector_bytes!("create_subscription"),
+        ),
+        (
+            "execute_subscription",
+            ink::selector_bytes!("execute_subscription"),
+        ),
+        ("version", ink::selector_bytes!("version")),
+        ("initialize", ink::selector_bytes!("initialize")),
+        (
+            "import_allowances",
+            ink::selector_bytes!("import_allowances"),
+        ),
+        ("is_fee_exempt", ink::selector_bytes!("is_fee_exempt")),
+        ("set_fee_exempt", ink::selector_bytes!("set_fee_exempt")),
+        ("bridge", ink::selector_bytes!("bridge")),
+        ("set_bridge", ink::selector_bytes!("set_bridge")),
+        (
+            "is_nonce_processed",
+            ink::selector_bytes!("is_nonce_processed"),
+        ),
+        ("bridge_mint", ink::selector_bytes!("bridge_mint")),
+        ("bridge_burn", ink::selector_bytes!("bridge_burn")),
+        (
+            "recent_transfers_of",
+            ink::selector_bytes!("recent_transfers_of"),
+        ),
+        ("holder_count", ink::selector_bytes!("holder_count")),
+        ("ever_holders", ink::selector_bytes!("ever_holders")),
+        (
+            "holder_distribution",
+            ink::selector_bytes!("holder_distribution"),
+        ),
+        ("max_holders", ink::selector_bytes!("max_holders")),
+        ("set_max_holders", ink::selector_bytes!("set_max_holders")),
+        ("approval_count", ink::selector_bytes!("approval_count")),
+        (
+            "max_approvals_per_owner",
+            ink::selector_bytes!("max_approvals_per_owner"),
+        ),
+        (
+            "set_max_approvals_per_owner",
+            ink::selector_bytes!("set_max_approvals_per_owner"),
+        ),
+        ("limits", ink::selector_bytes!("limits")),
+    ];
+
+    /// `SELECTOR_TABLE` as owned labels, for off-chain tooling
+    #[cfg(feature = "std")]
+    pub fn selector_table() -> Vec<(String, [u8; 4])> {
+        SELECTOR_TABLE
+            .iter()
+            .map(|(label, selector)| (String::from(*label), *selector))
+            .collect()
+    }
+
+    /// Guard for the batch messages
+    fn ensure_batch(len: usize) -> Result<()> {
+        if len > MAX_BATCH {
+            return Err(Error::BatchTooLarge);
+        }
+        Ok(())
+    }
+
+    /// Appends `value` under `snapshot_id` unless one was already recorded for it
+    fn record_checkpoint(checkpoints: &mut Checkpoints, snapshot_id: u32, value: Balance) -> bool {
+        if checkpoints.last().is_some_and(|(id, _)| *id >= snapshot_id) {
+            return false;
+        }
+        checkpoints.push((snapshot_id, value));
+        true
+    }
+
+    /// Reads the value `snapshot_id` saw, the first one recorded at or after it or `current` if nothing changed since
+    fn checkpoint_at(
+        checkpoints: &[(u32, Balance)],
+        snapshot_id: u32,
+        current: Balance,
+    ) -> Balance {
+        let index = checkpoints.partition_point(|(id, _)| *id < snapshot_id);
+        checkpoints.get(index).map_or(current, |(_, value)| *value)
+    }
+
+    /// Domain name mixed into every permit digest
+    pub const PERMIT_DOMAIN_NAME: &[u8] = b"MockDai";
+
+    /// Domain version every deployment starts at, raised by `bump_domain_version`
+    pub const PERMIT_DOMAIN_VERSION: u32 = 1;
+
+    /// Selector of the `check_transfer(from, to, amount) -> bool` message a policy contract exposes
+    pub const CHECK_TRANSFER_SELECTOR: [u8; 4] = ink::selector_bytes!("check_transfer");
+
+    /// Selector of the hook a registered receiver contract exposes to acknowledge incoming transfers
+    pub const ON_TOKENS_RECEIVED_SELECTOR: [u8; 4] = ink::selector_bytes!("on_tokens_received");
+
+    /// Selector of the `on_balance_change(account, new_balance)` hook a balance subscriber exposes
+    pub const ON_BALANCE_CHANGE_SELECTOR: [u8; 4] = ink::selector_bytes!("on_balance_change");
+
+    /// Most contracts that can subscribe to one account's balance changes
+    pub const MAX_BALANCE_SUBSCRIBERS: usize = 8;
+
+    /// Gas each `on_balance_change` callback gets, so a subscriber burning gas can't starve the transfer
+    pub const ON_BALANCE_CHANGE_GAS: u64 = 1_000_000_000;
+
+    /// Selector `approve_and_call` invokes on the spender unless the caller picks another one
+    pub const RECEIVE_APPROVAL_SELECTOR: [u8; 4] = ink::selector_bytes!("receive_approval");
+
+    /// ERC165-style interface id of `supports_interface` itself
+    pub const INTERFACE_ID_ERC165: [u8; 4] = ink::selector_bytes!("supports_interface");
+
+    /// ERC20-core interface id, the XOR of all its message selectors
+    pub const INTERFACE_ID_ERC20: [u8; 4] = xor_selectors(&[
+        ink::selector_bytes!("total_supply"),
+        ink::selector_bytes!("balance_of"),
+        ink::selector_bytes!("allowance"),
+        ink::selector_bytes!("transfer"),
+        ink::selector_bytes!("approve"),
+        ink::selector_bytes!("transfer_from"),
+    ]);
+
+    /// Permit interface id, the XOR of its message selectors
+    pub const INTERFACE_ID_PERMIT: [u8; 4] = xor_selectors(&[
+        ink::selector_bytes!("permit"),
+        ink::selector_bytes!("nonces"),
+        ink::selector_bytes!("domain_separator"),
+    ]);
+
+    /// Metadata interface id, the XOR of its message selectors
+    pub const INTERFACE_ID_METADATA: [u8; 4] = xor_selectors(&[
+        ink::selector_bytes!("name"),
+        ink::selector_bytes!("symbol"),
+        ink::selector_bytes!("decimals"),
+    ]);
+
+    /// Human-readable name of the token
+    pub const TOKEN_NAME: &str = "Mock Dai";
+
+    /// Ticker symbol of the token
+    pub const TOKEN_SYMBOL: &str = "DAI";
+
+    /// Folds a list of message selectors into a single interface id
+    const fn xor_selectors(selectors: &[[u8; 4]]) -> [u8; 4] {
+        let mut id = [0u8; 4];
+        let mut i = 0;
+        while i < selectors.len() {
+            let mut j = 0;
+            while j < 4 {
+                id[j] ^= selectors[i][j];
+                j += 1;
+            }
+            i += 1;
+        }
+        id
+    }
+
     impl MockDai {
         /// Let's create the mockDai token with an initial supply
         #[ink(constructor)]
         pub fn new(total_supply: Balance) -> Self {
+            Self::new_with_owner(total_supply, Self::env().caller())
+        }
+
+        /// Creates the token with the initial supply minted to the deployer but `owner` holding the
+        /// owner-only powers, for pipelines deploying from a throwaway key
+        #[ink(constructor)]
+        pub fn new_with_owner(total_supply: Balance, owner: AccountId) -> Self {
             let mut balances = Mapping::default();
             let caller = Self::env().caller();
             let allowances = Mapping::default();
 
             // mint total supply to caller e.g rex
             balances.insert(caller, &total_supply);
+            let mut holder_buckets = Vec::from([0; HOLDER_BUCKETS.len()]);
+            let mut has_received = Mapping::default();
+            if let Some(bucket) = bucket_of(total_supply) {
+                holder_buckets[bucket] += 1;
+                has_received.insert(caller, &());
+            }
 
             // fire the transfer event from the address(0) to address(rex) just like the EIP-20 specifies it
             Self::env().emit_event(Transfer {
@@ -69,6 +1509,121 @@ mod mock_dai {
                 total_supply,
                 balances,
                 allowances,
+                owner,
+                transfer_fee_bps: 0,
+                fee_splits: Vec::new(),
+                snapshot_id: 0,
+                account_snapshots: Mapping::default(),
+                total_supply_snapshots: Vec::new(),
+                dividends: Mapping::default(),
+                dividends_claimed: Mapping::default(),
+                burn_fee_bps: 0,
+                last_error_reason: None,
+                requires_ack: false,
+                ack_receivers: Mapping::default(),
+                pending_acks: Mapping::default(),
+                nonces: Mapping::default(),
+                allowance_uses: Mapping::default(),
+                max_allowance: Balance::MAX,
+                treasury: None,
+                staked: Mapping::default(),
+                total_staked: 0,
+                locks: Mapping::default(),
+                total_locked: 0,
+                transferable: true,
+                decimals: 18,
+                redenominations: Vec::new(),
+                balance_epochs: Mapping::default(),
+                last_pulls: Mapping::default(),
+                initialized: false,
+                fee_exempt: Mapping::default(),
+                bridge: None,
+                bridge_nonce: 0,
+                processed_nonces: Mapping::default(),
+                recent_transfers: Mapping::default(),
+                holder_count: u32::from(total_supply > 0),
+                max_holders: u32::MAX,
+                total_allowance_granted: Mapping::default(),
+                reflect_fees: false,
+                reflection_index: REFLECTION_PRECISION,
+                reflection_marks: Mapping::default(),
+                total_held: total_supply,
+                min_balance: 0,
+                supply_cap: 0,
+                min_transfer: 0,
+                max_transfer: 0,
+                exposure_checkpoints: Mapping::default(),
+                exposure_checkpoint_count: Mapping::default(),
+                minting_renounced: false,
+                policy: None,
+                holder_buckets,
+                treasury_mint_bps: 0,
+                daily_limit: Balance::MAX,
+                limit_window: DEFAULT_LIMIT_WINDOW,
+                sent_volume: Mapping::default(),
+                native_peg: 1,
+                burned_accidentally: 0,
+                minters: Mapping::default(),
+                launch_block: 0,
+                launch_cooldown: 0,
+                last_launch_transfer: Mapping::default(),
+                transfers_paused: false,
+                breaker_threshold: Balance::MAX,
+                breaker_window: DEFAULT_LIMIT_WINDOW,
+                outflow: (0, 0),
+                round_fee_up: false,
+                tags: Mapping::default(),
+                has_received,
+                fee_tiers: Vec::new(),
+                collected_fees: 0,
+                sub_balances: Mapping::default(),
+                total_in_subs: 0,
+                total_assets: 0,
+                vault_shares: Mapping::default(),
+                total_vault_shares: 0,
+                approvals_paused: false,
+                subscriptions: Mapping::default(),
+                next_subscription_id: 0,
+                spender_caps: Mapping::default(),
+                legacy_balances: Mapping::default(),
+                migrated: Mapping::default(),
+                approval_counts: Mapping::default(),
+                max_approvals_per_owner: u32::MAX,
+                mint_activation_block: None,
+                allowance_refills: Mapping::default(),
+                transfer_delay: 0,
+                transfer_requests: Mapping::default(),
+                next_transfer_request_id: 0,
+                total_queued: 0,
+                balance_subscribers: Mapping::default(),
+                token_symbols: Mapping::default(),
+                next_token_id: 1,
+                token_balances: Mapping::default(),
+                token_supplies: Mapping::default(),
+                price_usd: PRICE_PRECISION,
+                vote_locks: Mapping::default(),
+                processed_ops: Mapping::default(),
+                demurrage_bps: 0,
+                demurrage_since: 0,
+                last_touch: Mapping::default(),
+                ever_holders: u32::from(total_supply > 0),
+                classification: Mapping::default(),
+                class_fees: Mapping::default(),
+                allowance_recipients: Mapping::default(),
+                max_transfers_per_tx: u32::MAX,
+                stabilizer: None,
+                withdraw_gated: false,
+                withdraw_whitelist: Mapping::default(),
+                reentrancy_guard_enabled: true,
+                entered: false,
+                domain_version: PERMIT_DOMAIN_VERSION,
+                fees_to_reserve: false,
+                reserve: 0,
+                vestings: Mapping::default(),
+                total_vesting: 0,
+                total_dividends_unclaimed: 0,
+                dividends_unclaimed: Mapping::default(),
+                approved_subscribers: Mapping::default(),
             }
         }
 
@@ -82,120 +1637,7131 @@ mod mock_dai {
         /// Simply returns the token balance of a specified `account`
         #[ink(message)]
         pub fn balance_of(&self, account: AccountId) -> Balance {
-            self.balances.get(account).unwrap_or_default()
+            let balance = self.undecayed_balance(account);
+            balance - self.demurrage_on(&account, balance)
         }
 
-        /// Simply transfers mockDai tokens from caller to the receiving address `to`
-        pub fn transfer(&mut self, to: AccountId, amount: Balance) -> Result<()> {
-            let sender = self.env().caller();
-            self.transfer_from_to(&sender, &to, amount)
+        /// Simply returns the token balance of the caller, saving frontends from encoding their own `AccountId`
+        #[ink(message)]
+        pub fn my_balance(&self) -> Balance {
+            self.balance_of(self.env().caller())
         }
 
-        /// Approve spender to spend owner's tokens
-        pub fn approve(&mut self, spender: AccountId, amount: Balance) -> Result<()> {
-            let owner = self.env().caller();
-            self.allowances.insert((owner, spender), &amount);
-
-            self.env().emit_event(Approval {
-                owner,
-                spender,
-                amount,
-            });
-            Ok(())
+        /// Returns whether crediting `to` would create a new `balances` entry, charging a storage
+        /// deposit, rather than update an existing one. Emptied balances keep their entry
+        #[ink(message)]
+        pub fn would_create_entry(&self, to: AccountId) -> bool {
+            !self.balances.contains(to)
         }
 
-        /// Allowance function to figure out the allowances of an address as allocated by an owner
-        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
-            // if there is an allowance, it should return the allowance otherwise the default will kick in which is 0 -> that is why we use the `unwrap_or_default` method on this get method for allowance
-            self.allowances.get((owner, spender)).unwrap_or_default()
+        /// Returns whether `account`'s legacy balance has been moved into the current layout
+        #[ink(message)]
+        pub fn is_migrated(&self, account: AccountId) -> bool {
+            self.migrated.contains(account)
         }
 
-        /// Similar TransferFrom in Solidity to allow the calling third-party or address to take tokens of the specified `from` account supposing they've already been approved for it
-        pub fn transfer_from(&mut self, from: AccountId, to: AccountId, amount: Balance) -> Result<()> {
-            let msg_sender = self.env().caller();
-            let allowance = self.allowance(from, msg_sender);
+        /// Moves `account`'s legacy balance into the current layout ahead of its first write, callable
+        /// by anyone. The balance itself doesn't change and an account without one is left alone
+        #[ink(message)]
+        pub fn migrate_account(&mut self, account: AccountId) -> Result<()> {
+            self.track(|this| {
+                if this.legacy_balances.contains(account) {
+                    let balance = this.balance_of(account);
+                    this.set_balance(&account, balance);
+                }
+                Ok(())
+            })
+        }
 
-            if allowance < amount {
-                return Err(Error::InsufficientAllowance);
-            }
+        /// Simply transfers mockDai tokens from caller to the receiving address `to`
+        #[ink(message)]
+        pub fn transfer(&mut self, to: AccountId, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let sender = this.env().caller();
+                this.transfer_from_to(&sender, &to, amount)
+            })
+        }
 
-            self.transfer_from_to(&from, &to, amount)?;
-            self.allowances.insert((from, msg_sender), &(allowance - amount));
+        /// `transfer` under the PSP22-standardized selector, so PSP22 wallets and tooling can send
+        /// mockDai. `data` is accepted for the interface and ignored
+        #[ink(message, selector = 0xdb20f9f5)]
+        pub fn psp22_transfer(
+            &mut self,
+            to: AccountId,
+            value: Balance,
+            data: Vec<u8>,
+        ) -> Result<()> {
+            let _ = data;
+            self.transfer(to, value)
+        }
 
-            Ok(())
+        /// Sends `amount` to `to` and burns another `amount * burn_bps / 10_000` from the caller on top,
+        /// emitting a `Transfer` and a `Burn`. `burn_bps` is capped at `MAX_FEE_BPS`
+        #[ink(message)]
+        pub fn transfer_with_burn(
+            &mut self,
+            to: AccountId,
+            amount: Balance,
+            burn_bps: u16,
+        ) -> Result<()> {
+            self.track(|this| {
+                if burn_bps > MAX_FEE_BPS {
+                    return Err(Error::FeeTooHigh);
+                }
+                let sender = this.env().caller();
+                let burned = portion(amount, burn_bps, BPS_DENOMINATOR);
+                let total = amount.checked_add(burned).ok_or(Error::Overflow)?;
+                if this.balance_of(sender) < total {
+                    return Err(Error::InsufficientBalance);
+                }
+                this.transfer_from_to(&sender, &to, amount)?;
+                this.burn_from(&sender, burned)
+            })
         }
-        
 
-        /// Private function to handle the logic of tranfers
-        fn transfer_from_to(
+        /// Like `transfer`, but fails with `Error::Expired` once the chain is past `deadline_block`, so a
+        /// stale pending transaction can't execute late
+        #[ink(message)]
+        pub fn transfer_by(
             &mut self,
-            from: &AccountId,
-            to: &AccountId,
+            to: AccountId,
             amount: Balance,
+            deadline_block: BlockNumber,
         ) -> Result<()> {
-            let sender_balance = self.balance_of(*from);
+            self.track(|this| {
+                if this.env().block_number() > deadline_block {
+                    return Err(Error::Expired);
+                }
+                let sender = this.env().caller();
+                this.transfer_from_to(&sender, &to, amount)
+            })
+        }
 
-            if sender_balance < amount {
-                return Err(Error::InsufficientBalance);
-            }
+        /// Approve spender to spend owner's tokens
+        #[ink(message)]
+        pub fn approve(&mut self, spender: AccountId, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                this.approve_from(owner, spender, amount)
+            })
+        }
 
-            self.balances.insert(from, &(sender_balance - amount));
-            let to_balance = self.balance_of(*to);
-            self.balances.insert(to, &(to_balance + amount));
-            Ok(())
+        /// Sets `spender`'s allowance to the absolute `target` like `approve` and returns the signed
+        /// change applied, for UIs showing it. A change too large for an `i128` is an `Overflow`
+        #[ink(message)]
+        pub fn set_allowance(&mut self, spender: AccountId, target: Balance) -> Result<i128> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                let current = this.allowance(owner, spender);
+                let delta = if target >= current {
+                    i128::try_from(target - current)
+                } else {
+                    i128::try_from(current - target).map(|delta| -delta)
+                }
+                .map_err(|_| Error::Overflow)?;
+                this.approve_from(owner, spender, target)?;
+                Ok(delta)
+            })
         }
-    }
 
-    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
-    /// module and test functions are marked with a `#[test]` attribute.
-    /// The below code is technically just normal Rust code.
-    #[cfg(test)]
-    mod tests {
+        /// The safe-approve pattern in one atomic call: zeroes `spender`'s allowance, then sets it to
+        /// `amount`, firing an `Approval` for each step
+        #[ink(message)]
+        pub fn safe_approve(&mut self, spender: AccountId, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                this.approve_from(owner, spender, 0)?;
+                this.approve_from(owner, spender, amount)
+            })
+        }
+
+        /// Moves the caller's allowance for `old_spender` over to `new_spender`, for routers changing
+        /// address. The old one ends at zero and a limited-use allowance keeps its remaining uses
+        #[ink(message)]
+        pub fn migrate_allowance(
+            &mut self,
+            old_spender: AccountId,
+            new_spender: AccountId,
+        ) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                let amount = this.allowance(owner, old_spender);
+                this.ensure_allowance_cap(owner, new_spender, amount)?;
+                let uses = this.allowance_uses.take((owner, old_spender));
+                let refill = this.allowance_refills.take((owner, old_spender));
+                let recipient = this.allowance_recipients.take((owner, old_spender));
+                this.write_allowance(owner, old_spender, 0);
+
+                if let Some(uses) = uses {
+                    this.allowance_uses.insert((owner, new_spender), &uses);
+                } else {
+                    this.allowance_uses.remove((owner, new_spender));
+                }
+                if let Some(refill) = refill {
+                    this.allowance_refills.insert((owner, new_spender), &refill);
+                } else {
+                    this.allowance_refills.remove((owner, new_spender));
+                }
+                if let Some(recipient) = recipient {
+                    this.allowance_recipients
+                        .insert((owner, new_spender), &recipient);
+                } else {
+                    this.allowance_recipients.remove((owner, new_spender));
+                }
+                this.write_allowance(owner, new_spender, amount);
+                Ok(())
+            })
+        }
+
+        /// Race-free approve: sets `spender`'s allowance to `new_amount` only if it still is
+        /// `expected_current`, otherwise fails with `Error::AllowanceChanged` and leaves it as is
+        #[ink(message)]
+        pub fn compare_and_approve(
+            &mut self,
+            spender: AccountId,
+            expected_current: Balance,
+            new_amount: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                if this.allowance(owner, spender) != expected_current {
+                    return Err(Error::AllowanceChanged);
+                }
+                this.approve_from(owner, spender, new_amount)
+            })
+        }
+
+        /// Transfers `transfer_amount` to `to` and approves `spender` for `approve_amount` in one go,
+        /// for routers leaving a residual approval behind. If either fails neither happens
+        #[ink(message)]
+        pub fn transfer_and_approve(
+            &mut self,
+            to: AccountId,
+            transfer_amount: Balance,
+            spender: AccountId,
+            approve_amount: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                this.transfer_from_to(&owner, &to, transfer_amount)?;
+                this.approve_from(owner, spender, approve_amount)
+            })
+        }
+
+        /// Approves `spender` for `amount` over at most `uses` calls to `transfer_from`, the allowance is
+        /// revoked by the last of them whatever is left of it. Zero uses grants nothing
+        #[ink(message)]
+        pub fn approve_n_uses(
+            &mut self,
+            spender: AccountId,
+            amount: Balance,
+            uses: u32,
+        ) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                this.ensure_allowance_cap(owner, spender, amount)?;
+                if uses == 0 {
+                    this.allowance_uses.remove((owner, spender));
+                    this.write_allowance(owner, spender, 0);
+                } else {
+                    this.allowance_uses.insert((owner, spender), &uses);
+                    this.write_allowance(owner, spender, amount);
+                }
+                Ok(())
+            })
+        }
+
+        /// Approves `spender` for `amount` that it may only `transfer_from` the caller to `recipient`,
+        /// limiting the blast radius of the approval. A plain `approve` lifts the constraint
+        #[ink(message)]
+        pub fn approve_to_recipient(
+            &mut self,
+            spender: AccountId,
+            recipient: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                this.approve_from(owner, spender, amount)?;
+                this.allowance_recipients
+                    .insert((owner, spender), &recipient);
+                Ok(())
+            })
+        }
+
+        /// Returns the only recipient `spender` may send `owner`'s tokens to, if constrained
+        #[ink(message)]
+        pub fn allowance_recipient(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+        ) -> Option<AccountId> {
+            self.allowance_recipients.get((owner, spender))
+        }
+
+        /// Approves `spender` for `amount` as a standing order: after every `transfer_from` the allowance
+        /// refills back up to `refill_to`, or to the caller's balance if that is lower. A plain
+        /// `approve` ends the standing order
+        #[ink(message)]
+        pub fn approve_auto(
+            &mut self,
+            spender: AccountId,
+            amount: Balance,
+            refill_to: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                this.ensure_allowance_cap(owner, spender, amount.max(refill_to))?;
+                this.allowance_uses.remove((owner, spender));
+                this.allowance_refills.insert((owner, spender), &refill_to);
+                this.write_allowance(owner, spender, amount);
+                Ok(())
+            })
+        }
+
+        /// Returns the level `spender`'s allowance from `owner` refills to after each spend, if any
+        #[ink(message)]
+        pub fn allowance_refill(&self, owner: AccountId, spender: AccountId) -> Option<Balance> {
+            self.allowance_refills.get((owner, spender))
+        }
+
+        /// Returns the summed balance of `accounts`, for reconciling a known cohort against the total
+        /// supply. At most `MAX_BATCH` accounts per call
+        #[ink(message)]
+        pub fn sum_balances(&self, accounts: Vec<AccountId>) -> Result<Balance> {
+            ensure_batch(accounts.len())?;
+            accounts.iter().try_fold(0, |sum: Balance, account| {
+                sum.checked_add(self.balance_of(*account))
+                    .ok_or(Error::Overflow)
+            })
+        }
+
+        /// Returns the allowance of every owner × spender pair in row-major order, one row per owner.
+        /// At most `MAX_BATCH` pairs per call
+        #[ink(message)]
+        pub fn allowance_matrix(
+            &self,
+            owners: Vec<AccountId>,
+            spenders: Vec<AccountId>,
+        ) -> Result<Vec<Balance>> {
+            ensure_batch(owners.len().saturating_mul(spenders.len()))?;
+            Ok(owners
+                .iter()
+                .flat_map(|owner| {
+                    spenders
+                        .iter()
+                        .map(move |spender| self.allowance(*owner, *spender))
+                })
+                .collect())
+        }
+
+        /// Returns the sum of the outstanding allowances `owner` has granted, saturating at `Balance::MAX`
+        #[ink(message)]
+        pub fn total_approved_by(&self, owner: AccountId) -> Balance {
+            self.total_allowance_granted.get(owner).unwrap_or_default()
+        }
+
+        /// Returns `owner`'s granted allowance total as it stood when `block` began, checkpointed
+        /// per block the same way balances are per snapshot
+        #[ink(message)]
+        pub fn approval_exposure_at(&self, owner: AccountId, block: BlockNumber) -> Balance {
+            // binary search for the first checkpoint at or after `block`, reading one cell per step
+            let count = self
+                .exposure_checkpoint_count
+                .get(owner)
+                .unwrap_or_default();
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let middle = low + (high - low) / 2;
+                let (id, _) = self.exposure_checkpoint(owner, middle);
+                if id < block {
+                    low = middle + 1;
+                } else {
+                    high = middle;
+                }
+            }
+            if low == count {
+                return self.total_approved_by(owner);
+            }
+            self.exposure_checkpoint(owner, low).1
+        }
+
+        /// Returns the spends left on a limited-use allowance, `None` if it is unlimited
+        #[ink(message)]
+        pub fn allowance_uses(&self, owner: AccountId, spender: AccountId) -> Option<u32> {
+            self.allowance_uses.get((owner, spender))
+        }
+
+        /// Raises the caller's allowance for `spender` by `delta`, subject to the allowance cap
+        #[ink(message)]
+        pub fn increase_allowance(&mut self, spender: AccountId, delta: Balance) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                let amount = this
+                    .allowance(owner, spender)
+                    .checked_add(delta)
+                    .ok_or(Error::Overflow)?;
+                this.ensure_allowance_cap(owner, spender, amount)?;
+                this.write_allowance(owner, spender, amount);
+                Ok(())
+            })
+        }
+
+        /// Lowers the caller's allowance for `spender` by `delta`
+        #[ink(message)]
+        pub fn decrease_allowance(&mut self, spender: AccountId, delta: Balance) -> Result<()> {
+            self.track(|this| {
+                let owner = this.env().caller();
+                let amount = this
+                    .allowance(owner, spender)
+                    .checked_sub(delta)
+                    .ok_or(Error::InsufficientAllowance)?;
+                this.write_allowance(owner, spender, amount);
+                Ok(())
+            })
+        }
+
+        /// Allowance function to figure out the allowances of an address as allocated by an owner
+        #[ink(message)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            // if there is an allowance, it should return the allowance otherwise the default will kick in which is 0 -> that is why we use the `unwrap_or_default` method on this get method for allowance
+            self.allowances.get((owner, spender)).unwrap_or_default()
+        }
+
+        /// Similar TransferFrom in Solidity to allow the calling third-party or address to take tokens of the specified `from` account supposing they've already been approved for it
+        #[ink(message)]
+        pub fn transfer_from(&mut self, from: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let msg_sender = this.env().caller();
+                this.transfer_from_as(msg_sender, &from, &to, amount)
+            })
+        }
+
+        /// Pre-flight check running the validation `transfer_from` by the caller would (allowance,
+        /// pause, transfer policy, balance, minimum, limits and holder cap) and returning the error it
+        /// would fail with, without changing any state. Receiver hooks aren't called
+        #[ink(message)]
+        pub fn simulate_transfer_from(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            let spender = self.env().caller();
+            if self.allowance(from, spender) < amount {
+                return Err(Error::InsufficientAllowance);
+            }
+            if self
+                .allowance_recipient(from, spender)
+                .is_some_and(|recipient| recipient != to)
+            {
+                return Err(Error::RecipientNotAllowed);
+            }
+            self.ensure_transfer_allowed(&from, &to, amount)?;
+            if to != zero_account() && (from == to || self.balance_of(from) != amount) {
+                self.ensure_holder_room(&to, amount)?;
+            }
+            Ok(())
+        }
+
+        /// Returns the most `spender` could `transfer_from` out of `owner` right now: the allowance,
+        /// capped by the balance, the rest of the daily limit and the minimum balance left behind, and
+        /// zero while the token is paused or soulbound or `owner` can't send. Recipient-side checks
+        /// and the transfer policy depend on the transfer itself and aren't covered
+        #[ink(message)]
+        pub fn max_pullable(&self, owner: AccountId, spender: AccountId) -> Balance {
+            if !self.transferable
+                || self.transfers_paused
+                || self.env().block_number() < self.vote_lock_of(owner)
+                || self.ensure_launch_slot(&owner).is_err()
+            {
+                return 0;
+            }
+            let balance = self.balance_of(owner);
+            let daily_left = if self.daily_limit == Balance::MAX {
+                Balance::MAX
+            } else {
+                self.daily_limit.saturating_sub(self.sent_in_window(owner))
+            };
+            let mut pullable = self.allowance(owner, spender).min(balance).min(daily_left);
+            if self.max_transfer > 0 {
+                pullable = pullable.min(self.max_transfer);
+            }
+            // sending all of it is fine, leaving dust below the minimum isn't
+            if pullable < balance && balance - pullable < self.min_balance {
+                pullable = balance.saturating_sub(self.min_balance);
+            }
+            if pullable < self.min_transfer {
+                return 0;
+            }
+            pullable
+        }
+
+        /// Sweeps as much of `from`'s balance to `to` as the caller's allowance covers, the smaller of
+        /// the two, and returns that amount. Spends the allowance like `transfer_from`
+        #[ink(message)]
+        pub fn transfer_from_all(&mut self, from: AccountId, to: AccountId) -> Result<Balance> {
+            self.track(|this| {
+                let spender = this.env().caller();
+                let amount = this.allowance(from, spender).min(this.balance_of(from));
+                this.transfer_from_as(spender, &from, &to, amount)?;
+                Ok(amount)
+            })
+        }
+
+        /// ERC165-style feature detection, true for every interface id this token fully implements
+        #[ink(message)]
+        pub fn supports_interface(&self, interface_id: [u8; 4]) -> bool {
+            [
+                INTERFACE_ID_ERC165,
+                INTERFACE_ID_ERC20,
+                INTERFACE_ID_PERMIT,
+                INTERFACE_ID_METADATA,
+            ]
+            .contains(&interface_id)
+        }
+
+        /// Returns the owner account allowed to call the privileged messages
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Owner-only cleanup that moves every listed account's balance below `threshold` to the owner.
+        /// Accounts at or above the threshold (and empty ones) are skipped, a `Transfer` is fired per swept account.
+        /// At most `MAX_BATCH` accounts per call
+        #[ink(message)]
+        pub fn sweep_dust(&mut self, accounts: Vec<AccountId>, threshold: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                ensure_batch(accounts.len())?;
+                let owner = this.owner;
+
+                for account in accounts {
+                    let balance = this.balance_of(account);
+                    if account == owner || balance == 0 || balance >= threshold {
+                        continue;
+                    }
+                    this.move_balance(&account, &owner, balance)?;
+                }
+
+                Ok(())
+            })
+        }
+
+        /// Returns the transfer fee in basis points
+        #[ink(message)]
+        pub fn transfer_fee_bps(&self) -> u16 {
+            self.transfer_fee_bps
+        }
+
+        /// Returns the fee recipients and their share of the fee in basis points
+        #[ink(message)]
+        pub fn fee_splits(&self) -> Vec<(AccountId, u16)> {
+            self.fee_splits.clone()
+        }
+
+        /// Owner-only setter for the fee-on-transfer capped at `MAX_FEE_BPS`, split among `fee_splits` by
+        /// basis points. The splits have to sum to exactly `fee_bps`, so a fee of 0 takes no splits at all
+        #[ink(message)]
+        pub fn set_transfer_fee(
+            &mut self,
+            fee_bps: u16,
+            fee_splits: Vec<(AccountId, u16)>,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+
+                let total = fee_splits
+                    .iter()
+                    .try_fold(0u16, |total, (_, split_bps)| total.checked_add(*split_bps));
+                if fee_bps > MAX_FEE_BPS {
+                    return Err(Error::FeeTooHigh);
+                }
+                if total != Some(fee_bps) {
+                    return Err(Error::InvalidFeeConfig);
+                }
+
+                this.config_changed(
+                    ConfigKey::TransferFee,
+                    this.transfer_fee_bps.into(),
+                    fee_bps.into(),
+                );
+                this.transfer_fee_bps = fee_bps;
+                this.fee_splits = fee_splits;
+                Ok(())
+            })
+        }
+
+        /// Previews a transfer of `amount` from `from` to `to` under the current fee, exemption and tier
+        /// config without running it, returning `(net_received, fee)` with the burn fee counted in `fee`
+        #[ink(message)]
+        pub fn quote_transfer(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> (Balance, Balance) {
+            // sends to the zero address are held for recovery without fees
+            if to == zero_account() {
+                return (amount, 0);
+            }
+            let (fee, burned) = self.fees_for(&from, &to, amount);
+            (amount - fee - burned, fee + burned)
+        }
+
+        /// Returns the discounted transfer fee tiers as `(min_balance, fee_bps)`
+        #[ink(message)]
+        pub fn fee_tiers(&self) -> Vec<(Balance, u16)> {
+            self.fee_tiers.clone()
+        }
+
+        /// Owner-only setter for the loyalty fee tiers: a sender holding at least a tier's `min_balance`
+        /// pays its `fee_bps` instead of `transfer_fee_bps`, still split among the fee recipients. The
+        /// tiers go in strictly increasing balance order, each capped at `MAX_FEE_BPS`
+        #[ink(message)]
+        pub fn set_fee_tiers(&mut self, fee_tiers: Vec<(Balance, u16)>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                ensure_batch(fee_tiers.len())?;
+                if fee_tiers.iter().any(|(_, fee_bps)| *fee_bps > MAX_FEE_BPS) {
+                    return Err(Error::FeeTooHigh);
+                }
+                if fee_tiers.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                    return Err(Error::InvalidFeeConfig);
+                }
+                this.fee_tiers = fee_tiers.clone();
+                this.emit(FeeTiersChanged { fee_tiers });
+                Ok(())
+            })
+        }
+
+        /// Returns the recipient class of `account`, `CLASS_UNCLASSIFIED` unless the owner set one
+        #[ink(message)]
+        pub fn classification_of(&self, account: AccountId) -> u8 {
+            self.classification
+                .get(account)
+                .unwrap_or(CLASS_UNCLASSIFIED)
+        }
+
+        /// Owner-only setter classifying `account` as a transfer recipient, `CLASS_UNCLASSIFIED`
+        /// clears it
+        #[ink(message)]
+        pub fn set_classification(&mut self, account: AccountId, class: u8) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let old_class = this.classification_of(account);
+                match class {
+                    CLASS_UNCLASSIFIED => {
+                        this.classification.remove(account);
+                    }
+                    CLASS_EOA | CLASS_CONTRACT | CLASS_EXCHANGE => {
+                        this.classification.insert(account, &class);
+                    }
+                    _ => return Err(Error::InvalidFeeConfig),
+                }
+                this.account_setting_changed(
+                    ConfigKey::Classification,
+                    account,
+                    old_class.into(),
+                    class.into(),
+                );
+                Ok(())
+            })
+        }
+
+        /// Returns the transfer fee rate charged on transfers to recipients of `class`, if one is set
+        #[ink(message)]
+        pub fn class_fee(&self, class: u8) -> Option<u16> {
+            self.class_fees.get(class)
+        }
+
+        /// Owner-only setter for the transfer fee rate of a recipient class, taking precedence over the
+        /// base fee and the fee tiers. `None` drops it, the rate is capped at `MAX_FEE_BPS`
+        #[ink(message)]
+        pub fn set_class_fee(&mut self, class: u8, fee_bps: Option<u16>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let key = match class {
+                    CLASS_EOA => ConfigKey::ClassFeeEoa,
+                    CLASS_CONTRACT => ConfigKey::ClassFeeContract,
+                    CLASS_EXCHANGE => ConfigKey::ClassFeeExchange,
+                    _ => return Err(Error::InvalidFeeConfig),
+                };
+                if fee_bps.is_some_and(|fee_bps| fee_bps > MAX_FEE_BPS) {
+                    return Err(Error::FeeTooHigh);
+                }
+                // an unset rate is reported as 0
+                this.config_changed(
+                    key,
+                    this.class_fee(class).unwrap_or_default().into(),
+                    fee_bps.unwrap_or_default().into(),
+                );
+                match fee_bps {
+                    Some(fee_bps) => {
+                        this.class_fees.insert(class, &fee_bps);
+                    }
+                    None => {
+                        this.class_fees.remove(class);
+                    }
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns whether the fees round up instead of down
+        #[ink(message)]
+        pub fn round_fee_up(&self) -> bool {
+            self.round_fee_up
+        }
+
+        /// Owner-only setter for the fee rounding direction. Either way the recipient gets exactly
+        /// what is left after the fees, so nothing is created or lost
+        #[ink(message)]
+        pub fn set_round_fee_up(&mut self, round_fee_up: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::RoundFeeUp,
+                    this.round_fee_up.into(),
+                    round_fee_up.into(),
+                );
+                this.round_fee_up = round_fee_up;
+                Ok(())
+            })
+        }
+
+        /// Returns the share of every transfer that gets burned in basis points
+        #[ink(message)]
+        pub fn burn_fee_bps(&self) -> u16 {
+            self.burn_fee_bps
+        }
+
+        /// Owner-only setter for the deflationary burn fee, capped at `MAX_FEE_BPS`
+        #[ink(message)]
+        pub fn set_burn_fee(&mut self, burn_fee_bps: u16) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if burn_fee_bps > MAX_FEE_BPS {
+                    return Err(Error::FeeTooHigh);
+                }
+
+                this.config_changed(
+                    ConfigKey::BurnFee,
+                    this.burn_fee_bps.into(),
+                    burn_fee_bps.into(),
+                );
+                this.burn_fee_bps = burn_fee_bps;
+                Ok(())
+            })
+        }
+
+        /// Returns the smallest nonzero balance a transfer may leave its sender with
+        #[ink(message)]
+        pub fn min_balance(&self) -> Balance {
+            self.min_balance
+        }
+
+        /// Owner-only setter for the minimum balance, a transfer leaving the sender with less than it
+        /// but more than nothing is rejected. Balances already below it can still be sent in full
+        #[ink(message)]
+        pub fn set_min_balance(&mut self, min_balance: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(ConfigKey::MinBalance, this.min_balance, min_balance);
+                this.min_balance = min_balance;
+                Ok(())
+            })
+        }
+
+        /// Returns the most tokens that may ever be in circulation, 0 if there is no cap
+        #[ink(message)]
+        pub fn supply_cap(&self) -> Balance {
+            self.supply_cap
+        }
+
+        /// Owner-only setter for the supply cap every mint path is held to, 0 removes it. A cap below
+        /// the current supply is rejected with `Error::SupplyCapExceeded`
+        #[ink(message)]
+        pub fn set_supply_cap(&mut self, supply_cap: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if supply_cap > 0 && supply_cap < this.total_supply {
+                    return Err(Error::SupplyCapExceeded);
+                }
+                this.config_changed(ConfigKey::SupplyCap, this.supply_cap, supply_cap);
+                this.supply_cap = supply_cap;
+                Ok(())
+            })
+        }
+
+        /// Returns the smallest and largest amounts a single transfer may move, 0 where unbounded
+        #[ink(message)]
+        pub fn transfer_bounds(&self) -> (Balance, Balance) {
+            (self.min_transfer, self.max_transfer)
+        }
+
+        /// Owner-only setter for the per-transfer amount bounds, 0 leaving that side unbounded. A
+        /// minimum above a set maximum fails with `Error::InvalidTransferBounds`
+        #[ink(message)]
+        pub fn set_transfer_bounds(
+            &mut self,
+            min_transfer: Balance,
+            max_transfer: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if max_transfer > 0 && min_transfer > max_transfer {
+                    return Err(Error::InvalidTransferBounds);
+                }
+                this.config_changed(ConfigKey::MinTransfer, this.min_transfer, min_transfer);
+                this.config_changed(ConfigKey::MaxTransfer, this.max_transfer, max_transfer);
+                this.min_transfer = min_transfer;
+                this.max_transfer = max_transfer;
+                Ok(())
+            })
+        }
+
+        /// Returns whether transfers are paused
+        #[ink(message)]
+        pub fn paused(&self) -> bool {
+            self.transfers_paused
+        }
+
+        /// Owner-only pause of all transfers
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.set_paused(true)
+        }
+
+        /// Owner-only resumption of transfers, the only way out of a tripped circuit breaker. The
+        /// window's outflow starts over so the next transfer doesn't trip it again straight away
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.set_paused(false)
+        }
+
+        /// Owner-only switch for transfers alone, approvals stay open so holders can still revoke
+        /// them during an incident
+        #[ink(message)]
+        pub fn set_transfers_paused(&mut self, paused: bool) -> Result<()> {
+            self.set_paused(paused)
+        }
+
+        /// Returns whether approvals are paused
+        #[ink(message)]
+        pub fn approvals_paused(&self) -> bool {
+            self.approvals_paused
+        }
+
+        /// Owner-only switch for approvals alone, transfers keep moving
+        #[ink(message)]
+        pub fn set_approvals_paused(&mut self, paused: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::ApprovalsPaused,
+                    this.approvals_paused.into(),
+                    paused.into(),
+                );
+                this.approvals_paused = paused;
+                Ok(())
+            })
+        }
+
+        /// Returns the circuit breaker threshold and its window in blocks
+        #[ink(message)]
+        pub fn circuit_breaker(&self) -> (Balance, BlockNumber) {
+            (self.breaker_threshold, self.breaker_window)
+        }
+
+        /// Owner-only setter for the circuit breaker: once more than `threshold` is transferred within
+        /// a window of `window_blocks` blocks, transfers pause until the owner unpauses them.
+        /// `Balance::MAX` disables it, a window of 0 is taken as 1
+        #[ink(message)]
+        pub fn set_circuit_breaker(
+            &mut self,
+            threshold: Balance,
+            window_blocks: BlockNumber,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let window_blocks = window_blocks.max(1);
+                this.config_changed(
+                    ConfigKey::BreakerThreshold,
+                    this.breaker_threshold,
+                    threshold,
+                );
+                this.config_changed(
+                    ConfigKey::BreakerWindow,
+                    this.breaker_window.into(),
+                    window_blocks.into(),
+                );
+                this.breaker_threshold = threshold;
+                this.breaker_window = window_blocks;
+                Ok(())
+            })
+        }
+
+        /// Returns the launch block and the cooldown after it in blocks
+        #[ink(message)]
+        pub fn launch(&self) -> (BlockNumber, BlockNumber) {
+            (self.launch_block, self.launch_cooldown)
+        }
+
+        /// Owner-only setter for the fair-launch cooldown: for `cooldown` blocks from `launch_block`
+        /// every account may send once per block, extra transfers fail with `Error::TransferCooldown`
+        #[ink(message)]
+        pub fn set_launch(
+            &mut self,
+            launch_block: BlockNumber,
+            cooldown: BlockNumber,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::LaunchBlock,
+                    this.launch_block.into(),
+                    launch_block.into(),
+                );
+                this.config_changed(
+                    ConfigKey::LaunchCooldown,
+                    this.launch_cooldown.into(),
+                    cooldown.into(),
+                );
+                this.launch_block = launch_block;
+                this.launch_cooldown = cooldown;
+                Ok(())
+            })
+        }
+
+        /// Returns the daily transfer limit and its window in blocks
+        #[ink(message)]
+        pub fn daily_limit(&self) -> (Balance, BlockNumber) {
+            (self.daily_limit, self.limit_window)
+        }
+
+        /// Returns what `account` sent so far in the current limit window
+        #[ink(message)]
+        pub fn sent_in_window(&self, account: AccountId) -> Balance {
+            let window = self.env().block_number() / self.limit_window;
+            match self.sent_volume.get(account) {
+                Some((last_window, sent)) if last_window == window => sent,
+                _ => 0,
+            }
+        }
+
+        /// Owner-only setter capping what every account may send per window of `window_blocks`
+        /// blocks, counted from block 0. `Balance::MAX` lifts the cap, a window of 0 is taken as 1
+        #[ink(message)]
+        pub fn set_daily_limit(
+            &mut self,
+            limit: Balance,
+            window_blocks: BlockNumber,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let window_blocks = window_blocks.max(1);
+                this.config_changed(ConfigKey::DailyLimit, this.daily_limit, limit);
+                this.config_changed(
+                    ConfigKey::LimitWindow,
+                    this.limit_window.into(),
+                    window_blocks.into(),
+                );
+                this.daily_limit = limit;
+                this.limit_window = window_blocks;
+                Ok(())
+            })
+        }
+
+        /// Returns whether the transfer fee is reflected to every holder
+        #[ink(message)]
+        pub fn reflect_fees(&self) -> bool {
+            self.reflect_fees
+        }
+
+        /// Returns the reflection index, scaled by `REFLECTION_PRECISION`
+        #[ink(message)]
+        pub fn reflection_index(&self) -> Balance {
+            self.reflection_index
+        }
+
+        /// Owner-only switch reflecting the transfer fee pro-rata to every holder, the sender and
+        /// recipient included, instead of paying it to the fee splits. Reflected growth isn't written
+        /// per account, so it doesn't show in snapshots taken before the holder's next balance change
+        #[ink(message)]
+        pub fn set_reflect_fees(&mut self, reflect_fees: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::ReflectFees,
+                    this.reflect_fees.into(),
+                    reflect_fees.into(),
+                );
+                this.reflect_fees = reflect_fees;
+                Ok(())
+            })
+        }
+
+        /// Owner-only message recording the current balances under a new snapshot id, which is returned
+        #[ink(message)]
+        pub fn snapshot(&mut self) -> Result<u32> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.snapshot_id += 1;
+
+                this.emit(Snapshot {
+                    id: this.snapshot_id,
+                });
+                Ok(this.snapshot_id)
+            })
+        }
+
+        /// Returns the balance `account` held when the snapshot `snapshot_id` was taken
+        #[ink(message)]
+        pub fn balance_at(&self, account: AccountId, snapshot_id: u32) -> Result<Balance> {
+            self.ensure_snapshot(snapshot_id)?;
+            let snapshots = self.account_snapshots.get(account).unwrap_or_default();
+            Ok(checkpoint_at(
+                &snapshots,
+                snapshot_id,
+                self.balance_of(account),
+            ))
+        }
+
+        /// Returns the total supply when the snapshot `snapshot_id` was taken
+        #[ink(message)]
+        pub fn total_supply_at(&self, snapshot_id: u32) -> Result<Balance> {
+            self.ensure_snapshot(snapshot_id)?;
+            Ok(checkpoint_at(
+                &self.total_supply_snapshots,
+                snapshot_id,
+                self.total_supply,
+            ))
+        }
+
+        /// Owner-only dividend funding: takes a snapshot as the epoch and escrows `amount` from the
+        /// owner for the holders of that snapshot to claim pro-rata. Returns the new epoch
+        #[ink(message)]
+        pub fn distribute(&mut self, amount: Balance) -> Result<u32> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let owner = this.owner;
+                let owner_balance = this.balance_of(owner);
+                if owner_balance < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+
+                // snapshot before funding so the epoch reflects the holdings the dividend is paid on
+                let epoch = this.snapshot()?;
+                this.set_balance(&owner, owner_balance - amount);
+                this.dividends.insert(epoch, &amount);
+                this.dividends_unclaimed.insert(epoch, &amount);
+                this.total_dividends_unclaimed += amount;
+
+                this.emit(DividendDistributed { epoch, amount });
+                Ok(epoch)
+            })
+        }
+
+        /// Returns the dividend funded for `epoch`
+        #[ink(message)]
+        pub fn dividend_of(&self, epoch: u32) -> Balance {
+            self.dividends.get(epoch).unwrap_or_default()
+        }
+
+        /// Pays the caller `amount * balance_at(epoch) / total_supply_at(epoch)` of the dividend, once per epoch
+        #[ink(message)]
+        pub fn claim_dividend(&mut self, epoch: u32) -> Result<Balance> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let amount = this.dividends.get(epoch).ok_or(Error::DividendNotFound)?;
+                let unclaimed = this
+                    .dividends_unclaimed
+                    .get(epoch)
+                    .ok_or(Error::DividendSwept)?;
+                if this.dividends_claimed.contains((caller, epoch)) {
+                    return Err(Error::DividendAlreadyClaimed);
+                }
+
+                let share = mul_div(
+                    amount,
+                    this.balance_at(caller, epoch)?,
+                    this.total_supply_at(epoch)?,
+                )?;
+                let unclaimed = unclaimed.checked_sub(share).ok_or(Error::Overflow)?;
+                this.dividends_claimed.insert((caller, epoch), &());
+                this.dividends_unclaimed.insert(epoch, &unclaimed);
+                this.total_dividends_unclaimed -= share;
+                let balance = this.balance_of(caller);
+                this.set_balance(&caller, balance + share);
+
+                this.emit(DividendClaimed {
+                    account: caller,
+                    epoch,
+                    amount: share,
+                });
+                Ok(share)
+            })
+        }
+
+        /// Returns the part of the dividend of `epoch` still waiting to be claimed
+        #[ink(message)]
+        pub fn dividend_unclaimed(&self, epoch: u32) -> Balance {
+            self.dividends_unclaimed.get(epoch).unwrap_or_default()
+        }
+
+        /// Owner-only sweep of the unclaimed rest of the dividend of `epoch` back to the owner, returned.
+        /// Meant for the rounding remainder once the holders claimed, as it closes the epoch for good
+        #[ink(message)]
+        pub fn sweep_dividend(&mut self, epoch: u32) -> Result<Balance> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if !this.dividends.contains(epoch) {
+                    return Err(Error::DividendNotFound);
+                }
+                let amount = this
+                    .dividends_unclaimed
+                    .take(epoch)
+                    .ok_or(Error::DividendSwept)?;
+                this.total_dividends_unclaimed -= amount;
+                let owner = this.owner;
+                let balance = this.balance_of(owner);
+                this.set_balance(&owner, balance + amount);
+
+                this.emit(DividendSwept { epoch, amount });
+                Ok(amount)
+            })
+        }
+
+        /// Canonical blake2 commitment of a transfer receipt for off-chain relayers to sign, reads no state
+        #[ink(message)]
+        pub fn transfer_receipt_hash(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+            nonce: u64,
+        ) -> [u8; 32] {
+            self.env()
+                .hash_encoded::<Blake2x256, _>(&(from, to, amount, nonce))
+        }
+
+        /// Returns why the latest mutating message failed, `None` if it succeeded.
+        /// Keep in mind a failing message reverts its storage writes on-chain, this reason included,
+        /// so it only survives where messages are called directly such as in off-chain tests
+        #[ink(message)]
+        pub fn last_error_reason(&self) -> Option<String> {
+            self.last_error_reason.clone()
+        }
+
+        /// Returns whether registered receiver contracts have to acknowledge incoming transfers
+        #[ink(message)]
+        pub fn requires_ack(&self) -> bool {
+            self.requires_ack
+        }
+
+        /// Owner-only toggle for the acknowledgement mode
+        #[ink(message)]
+        pub fn set_requires_ack(&mut self, enabled: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::RequiresAck,
+                    this.requires_ack.into(),
+                    enabled.into(),
+                );
+                this.requires_ack = enabled;
+                Ok(())
+            })
+        }
+
+        /// Returns whether `account` opted in to acknowledging its incoming transfers
+        #[ink(message)]
+        pub fn is_ack_receiver(&self, account: AccountId) -> bool {
+            self.ack_receivers.contains(account)
+        }
+
+        /// Lets the caller opt in or out of acknowledging its incoming transfers. Once opted in, a
+        /// contract gets `on_tokens_received(from, amount)` called on every transfer to it and has to
+        /// call back `acknowledge_receipt` from inside that hook or the transfer reverts. EOAs are exempt
+        #[ink(message)]
+        pub fn register_ack_receiver(&mut self, registered: bool) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                if registered {
+                    this.ack_receivers.insert(caller, &());
+                } else {
+                    this.ack_receivers.remove(caller);
+                }
+                Ok(())
+            })
+        }
+
+        /// Called back by a receiver contract from its `on_tokens_received` hook to accept the transfer
+        #[ink(message)]
+        pub fn acknowledge_receipt(&mut self) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                if !this.pending_acks.contains(caller) {
+                    return Err(Error::NoPendingReceipt);
+                }
+                this.pending_acks.remove(caller);
+                Ok(())
+            })
+        }
+
+        /// Returns the nonce the next permit signed by `owner` has to use
+        #[ink(message)]
+        pub fn nonces(&self, owner: AccountId) -> u64 {
+            self.nonces.get(owner).unwrap_or_default()
+        }
+
+        /// Returns the canonical Merkle leaf for an airdrop claim of `amount` by `account`: the
+        /// Blake2x256 hash of the SCALE-encoded `(account, amount)`, that is the 32 account bytes
+        /// followed by the amount as 16 little-endian bytes
+        #[ink(message)]
+        pub fn claim_leaf(&self, account: AccountId, amount: Balance) -> [u8; 32] {
+            self.env().hash_encoded::<Blake2x256, _>(&(account, amount))
+        }
+
+        /// Returns the permit nonce of every owner in `owners`, in order, for relayers preparing many
+        /// signatures at once. At most `MAX_BATCH` owners per call
+        #[ink(message)]
+        pub fn nonces_of(&self, owners: Vec<AccountId>) -> Result<Vec<u64>> {
+            ensure_batch(owners.len())?;
+            Ok(owners.into_iter().map(|owner| self.nonces(owner)).collect())
+        }
+
+        /// Returns the domain separator binding permit signatures to this token deployment
+        #[ink(message)]
+        pub fn domain_separator(&self) -> [u8; 32] {
+            self.env().hash_encoded::<Blake2x256, _>(&(
+                PERMIT_DOMAIN_NAME,
+                self.domain_version,
+                self.env().account_id(),
+            ))
+        }
+
+        /// Returns the domain version currently mixed into the permit digests
+        #[ink(message)]
+        pub fn domain_version(&self) -> u32 {
+            self.domain_version
+        }
+
+        /// Owner-only bump of the permit domain version, e.g. after an upgrade. Every permit signed
+        /// under the previous version stops validating
+        #[ink(message)]
+        pub fn bump_domain_version(&mut self) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let version = this.domain_version.checked_add(1).ok_or(Error::Overflow)?;
+                this.config_changed(
+                    ConfigKey::DomainVersion,
+                    this.domain_version.into(),
+                    version.into(),
+                );
+                this.domain_version = version;
+                Ok(())
+            })
+        }
+
+        /// Returns the digest `owner` has to sign with its ECDSA key for a permit at its current nonce
+        #[ink(message)]
+        pub fn permit_digest(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            single_use: bool,
+        ) -> [u8; 32] {
+            self.env().hash_encoded::<Blake2x256, _>(&(
+                self.domain_separator(),
+                owner,
+                spender,
+                value,
+                self.nonces(owner),
+                deadline,
+                single_use,
+            ))
+        }
+
+        /// Gasless approval: sets `spender`'s allowance to `value` from an ECDSA `signature` of `owner`
+        /// over `permit_digest`, submitted by anyone before the `deadline` timestamp
+        #[ink(message)]
+        pub fn permit(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_allowance_cap(owner, spender, value)?;
+                this.use_permit(owner, spender, value, deadline, signature, false)?;
+                this.allowance_uses.remove((owner, spender));
+                this.write_allowance(owner, spender, value);
+                Ok(())
+            })
+        }
+
+        /// `permit` for signers handing out the split `v`, `r`, `s` components instead of a 65-byte
+        /// signature. `v` is taken either as the raw recovery id or in the EIP-2612 `27`/`28` form
+        #[ink(message)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn permit_vrs(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            v: u8,
+            r: [u8; 32],
+            s: [u8; 32],
+        ) -> Result<()> {
+            let mut signature = [0u8; 65];
+            signature[..32].copy_from_slice(&r);
+            signature[32..64].copy_from_slice(&s);
+            signature[64] = if v >= 27 { v - 27 } else { v };
+            self.permit(owner, spender, value, deadline, signature)
+        }
+
+        /// Like `permit` but the allowance is fully revoked by the first `transfer_from` spending it,
+        /// whatever is left of `value`. Signed separately so a plain permit signature can't be used here
+        #[ink(message)]
+        pub fn permit_single_use(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_allowance_cap(owner, spender, value)?;
+                this.use_permit(owner, spender, value, deadline, signature, true)?;
+                this.allowance_uses.insert((owner, spender), &1);
+                this.write_allowance(owner, spender, value);
+                Ok(())
+            })
+        }
+
+        /// Owner-only compliance action zeroing `spender`'s allowance from `owner` without the holder's
+        /// consent, together with any limited-use or refill terms attached to it
+        #[ink(message)]
+        pub fn force_revoke(&mut self, owner: AccountId, spender: AccountId) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.allowance_uses.remove((owner, spender));
+                this.allowance_refills.remove((owner, spender));
+                this.allowance_recipients.remove((owner, spender));
+                this.write_allowance(owner, spender, 0);
+                Ok(())
+            })
+        }
+
+        /// Returns the highest allowance an owner can grant
+        #[ink(message)]
+        pub fn max_allowance(&self) -> Balance {
+            self.max_allowance
+        }
+
+        /// Owner-only setter for the allowance cap, `Balance::MAX` disables it
+        #[ink(message)]
+        pub fn set_max_allowance(&mut self, max_allowance: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(ConfigKey::MaxAllowance, this.max_allowance, max_allowance);
+                this.max_allowance = max_allowance;
+                Ok(())
+            })
+        }
+
+        /// Returns the highest allowance any owner can grant `spender`, next to `max_allowance`
+        #[ink(message)]
+        pub fn spender_cap(&self, spender: AccountId) -> Balance {
+            self.spender_caps.get(spender).unwrap_or(Balance::MAX)
+        }
+
+        /// Owner-only setter capping every allowance for a known-risky `spender`, `Balance::MAX`
+        /// removes the cap. Allowances already above it stay until they're next written
+        #[ink(message)]
+        pub fn set_spender_cap(&mut self, spender: AccountId, cap: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let old_cap = this.spender_cap(spender);
+                if cap == Balance::MAX {
+                    this.spender_caps.remove(spender);
+                } else {
+                    this.spender_caps.insert(spender, &cap);
+                }
+                this.account_setting_changed(ConfigKey::SpenderCap, spender, old_cap, cap);
+                Ok(())
+            })
+        }
+
+        /// Returns the treasury account, if one is configured
+        #[ink(message)]
+        pub fn treasury(&self) -> Option<AccountId> {
+            self.treasury
+        }
+
+        /// Owner-only setter for the treasury account
+        #[ink(message)]
+        pub fn set_treasury(&mut self, treasury: Option<AccountId>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.address_changed(ConfigKey::Treasury, this.treasury, treasury);
+                this.treasury = treasury;
+                Ok(())
+            })
+        }
+
+        /// Returns the policy contract transfers are checked against, if any
+        #[ink(message)]
+        pub fn policy(&self) -> Option<AccountId> {
+            self.policy
+        }
+
+        /// Owner-only setter for the policy contract. While set, every transfer first calls its
+        /// `check_transfer(from, to, amount)` and fails with `Error::PolicyRejected` unless it returns `true`
+        #[ink(message)]
+        pub fn set_policy(&mut self, policy: Option<AccountId>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.address_changed(ConfigKey::Policy, this.policy, policy);
+                this.policy = policy;
+                Ok(())
+            })
+        }
+
+        /// Returns the label the owner tagged `account` with, if any
+        #[ink(message)]
+        pub fn tag_of(&self, account: AccountId) -> Option<Vec<u8>> {
+            self.tags.get(account)
+        }
+
+        /// Owner-only setter tagging `account` with an opaque label of at most `MAX_TAG_LEN` bytes,
+        /// such as "whale" or "bot". An empty tag removes it
+        #[ink(message)]
+        pub fn set_tag(&mut self, account: AccountId, tag: Vec<u8>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if tag.len() > MAX_TAG_LEN {
+                    return Err(Error::TagTooLong);
+                }
+                if tag.is_empty() {
+                    this.tags.remove(account);
+                } else {
+                    this.tags.insert(account, &tag);
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns the tokens `account` has staked
+        #[ink(message)]
+        pub fn staked_of(&self, account: AccountId) -> Balance {
+            self.staked.get(account).unwrap_or_default()
+        }
+
+        /// Moves `amount` of the caller's balance into staking
+        #[ink(message)]
+        pub fn stake(&mut self, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let balance = this.balance_of(caller);
+                if balance < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+
+                this.set_balance(&caller, balance - amount);
+                this.staked
+                    .insert(caller, &(this.staked_of(caller) + amount));
+                this.total_staked += amount;
+
+                this.emit(Staked {
+                    account: caller,
+                    amount,
+                });
+                Ok(())
+            })
+        }
+
+        /// Moves `amount` of the caller's stake back into its balance
+        #[ink(message)]
+        pub fn unstake(&mut self, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let staked = this.staked_of(caller);
+                if staked < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+
+                this.staked.insert(caller, &(staked - amount));
+                this.total_staked -= amount;
+                let balance = this.balance_of(caller);
+                this.set_balance(&caller, balance + amount);
+
+                this.emit(Unstaked {
+                    account: caller,
+                    amount,
+                });
+                Ok(())
+            })
+        }
+
+        /// Returns the assets backing the vault shares
+        #[ink(message)]
+        pub fn total_assets(&self) -> Balance {
+            self.total_assets
+        }
+
+        /// Returns the vault shares `account` deposited for and hasn't redeemed
+        #[ink(message)]
+        pub fn vault_shares_of(&self, account: AccountId) -> Balance {
+            self.vault_shares.get(account).unwrap_or_default()
+        }
+
+        /// Returns the sum of all vault shares
+        #[ink(message)]
+        pub fn total_vault_shares(&self) -> Balance {
+            self.total_vault_shares
+        }
+
+        /// Returns the shares `assets` are worth, priced against the vault shares only so tokens minted
+        /// outside the vault don't dilute it. An empty vault converts 1:1
+        #[ink(message)]
+        pub fn convert_to_shares(&self, assets: Balance) -> Balance {
+            if self.total_vault_shares == 0 || self.total_assets == 0 {
+                return assets;
+            }
+            mul_div(assets, self.total_vault_shares, self.total_assets).unwrap_or(Balance::MAX)
+        }
+
+        /// Returns the assets `shares` are worth, the inverse of `convert_to_shares`
+        #[ink(message)]
+        pub fn convert_to_assets(&self, shares: Balance) -> Balance {
+            if self.total_vault_shares == 0 || self.total_assets == 0 {
+                return shares;
+            }
+            mul_div(shares, self.total_assets, self.total_vault_shares).unwrap_or(Balance::MAX)
+        }
+
+        /// Minter-only deposit of `assets` into the vault, minting the caller the shares they're worth,
+        /// returned. It mints like any other mint, so the treasury takes its cut on top
+        #[ink(message)]
+        pub fn deposit(&mut self, assets: Balance) -> Result<Balance> {
+            self.track(|this| {
+                this.ensure_minter()?;
+                let caller = this.env().caller();
+                let shares = this.convert_to_shares(assets);
+                let total_assets = this
+                    .total_assets
+                    .checked_add(assets)
+                    .ok_or(Error::Overflow)?;
+                let total_vault_shares = this
+                    .total_vault_shares
+                    .checked_add(shares)
+                    .ok_or(Error::Overflow)?;
+                this.mint_with_treasury_cut(&caller, shares)?;
+                this.total_assets = total_assets;
+                this.total_vault_shares = total_vault_shares;
+                let held = this.vault_shares_of(caller);
+                this.vault_shares.insert(caller, &(held + shares));
+                Ok(shares)
+            })
+        }
+
+        /// Burns `shares` of the caller's vault shares and takes the assets they're worth out of the
+        /// vault, returned
+        #[ink(message)]
+        pub fn redeem(&mut self, shares: Balance) -> Result<Balance> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let held = this.vault_shares_of(caller);
+                if shares > held {
+                    return Err(Error::InsufficientVaultShares);
+                }
+                let assets = this.convert_to_assets(shares).min(this.total_assets);
+                this.burn_from(&caller, shares)?;
+                this.total_assets -= assets;
+                this.total_vault_shares -= shares;
+                this.vault_shares.insert(caller, &(held - shares));
+                Ok(assets)
+            })
+        }
+
+        /// Owner-only report of `assets` of yield earned by the vault, raising what every share is worth
+        #[ink(message)]
+        pub fn accrue_assets(&mut self, assets: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.total_assets = this
+                    .total_assets
+                    .checked_add(assets)
+                    .ok_or(Error::Overflow)?;
+                Ok(())
+            })
+        }
+
+        /// Returns the tokens `account` holds in its sub-account `sub_id`
+        #[ink(message)]
+        pub fn sub_balance_of(&self, account: AccountId, sub_id: u32) -> Balance {
+            self.sub_balances.get((account, sub_id)).unwrap_or_default()
+        }
+
+        /// Returns the tokens held across every account's sub-accounts
+        #[ink(message)]
+        pub fn total_in_subs(&self) -> Balance {
+            self.total_in_subs
+        }
+
+        /// Moves `amount` of the caller's liquid balance into its sub-account `sub_id`. Sub-account
+        /// tokens don't show in `balance_of`, which only reports the liquid part
+        #[ink(message)]
+        pub fn deposit_to_sub(&mut self, sub_id: u32, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                this.move_sub(caller, None, Some(sub_id), amount)
+            })
+        }
+
+        /// Moves `amount` out of the caller's sub-account `sub_id` back into its liquid balance
+        #[ink(message)]
+        pub fn withdraw_from_sub(&mut self, sub_id: u32, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                this.move_sub(caller, Some(sub_id), None, amount)
+            })
+        }
+
+        /// Moves `amount` between two of the caller's sub-accounts
+        #[ink(message)]
+        pub fn move_between_subs(
+            &mut self,
+            from_sub: u32,
+            to_sub: u32,
+            amount: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                this.move_sub(caller, Some(from_sub), Some(to_sub), amount)
+            })
+        }
+
+        /// Returns the tokens `account` has locked and the block they unlock at
+        #[ink(message)]
+        pub fn lock_of(&self, account: AccountId) -> (Balance, BlockNumber) {
+            self.locks.get(account).unwrap_or_default()
+        }
+
+        /// Locks `amount` of the caller's balance until block `until`. Topping up an existing lock
+        /// keeps whichever unlock block is later
+        #[ink(message)]
+        pub fn lock(&mut self, amount: Balance, until: BlockNumber) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let balance = this.balance_of(caller);
+                if balance < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+
+                let (locked, locked_until) = this.lock_of(caller);
+                let until = until.max(locked_until);
+                this.set_balance(&caller, balance - amount);
+                this.locks.insert(caller, &(locked + amount, until));
+                this.total_locked += amount;
+
+                this.emit(Locked {
+                    account: caller,
+                    amount,
+                    until,
+                });
+                Ok(())
+            })
+        }
+
+        /// Releases the caller's whole lock back into its balance once its unlock block is reached
+        #[ink(message)]
+        pub fn release_lock(&mut self) -> Result<Balance> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let (locked, until) = this.lock_of(caller);
+                if this.env().block_number() < until {
+                    return Err(Error::StillLocked);
+                }
+
+                this.locks.remove(caller);
+                this.total_locked -= locked;
+                let balance = this.balance_of(caller);
+                this.set_balance(&caller, balance + locked);
+
+                this.emit(Unlocked {
+                    account: caller,
+                    amount: locked,
+                });
+                Ok(locked)
+            })
+        }
+
+        /// Returns `account`'s vesting schedule, if it has one left to claim
+        #[ink(message)]
+        pub fn vesting_of(&self, account: AccountId) -> Option<Vesting> {
+            self.vestings.get(account)
+        }
+
+        /// Returns how much of `account`'s schedule has vested so far, claimed or not: nothing before
+        /// the cliff, then linearly from `start` up to the whole total once `duration` has passed
+        #[ink(message)]
+        pub fn vested_amount(&self, account: AccountId) -> Balance {
+            let Some((total, start, cliff, duration, _)) = self.vestings.get(account) else {
+                return 0;
+            };
+            let elapsed = self.env().block_number().saturating_sub(start);
+            if elapsed < cliff {
+                return 0;
+            }
+            if elapsed >= duration {
+                return total;
+            }
+            mul_div(total, elapsed.into(), duration.into()).unwrap_or(total)
+        }
+
+        /// Owner-only grant vesting `total` of the owner's tokens to `account` over `duration` blocks
+        /// from block `start`, nothing of it claimable for the first `cliff` blocks. It fires a
+        /// `Locked` until the schedule ends
+        #[ink(message)]
+        pub fn create_vesting(
+            &mut self,
+            account: AccountId,
+            total: Balance,
+            start: BlockNumber,
+            cliff: BlockNumber,
+            duration: BlockNumber,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if duration == 0 || cliff > duration {
+                    return Err(Error::InvalidSchedule);
+                }
+                let until = start.checked_add(duration).ok_or(Error::Overflow)?;
+                if this.vestings.contains(account) {
+                    return Err(Error::VestingExists);
+                }
+                let owner = this.env().caller();
+                let balance = this.balance_of(owner);
+                if balance < total {
+                    return Err(Error::InsufficientBalance);
+                }
+
+                this.set_balance(&owner, balance - total);
+                this.vestings
+                    .insert(account, &(total, start, cliff, duration, 0));
+                this.total_vesting += total;
+
+                this.emit(Locked {
+                    account,
+                    amount: total,
+                    until,
+                });
+                Ok(())
+            })
+        }
+
+        /// Releases whatever the caller's schedule vested since its last claim into its balance,
+        /// returning how much. Fails with `Error::StillLocked` while nothing new has vested
+        #[ink(message)]
+        pub fn claim_vested(&mut self) -> Result<Balance> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let vested = this.vested_amount(caller);
+                let Some((total, start, cliff, duration, claimed)) = this.vestings.get(caller)
+                else {
+                    return Err(Error::StillLocked);
+                };
+                let amount = vested - claimed;
+                if amount == 0 {
+                    return Err(Error::StillLocked);
+                }
+
+                if vested == total {
+                    this.vestings.remove(caller);
+                } else {
+                    this.vestings
+                        .insert(caller, &(total, start, cliff, duration, vested));
+                }
+                this.total_vesting -= amount;
+                let balance = this.balance_of(caller);
+                this.set_balance(&caller, balance + amount);
+
+                this.emit(Unlocked {
+                    account: caller,
+                    amount,
+                });
+                Ok(amount)
+            })
+        }
+
+        /// Returns the demurrage rate in basis points of an idle balance per block
+        #[ink(message)]
+        pub fn demurrage_bps_per_block(&self) -> u16 {
+            self.demurrage_bps
+        }
+
+        /// Owner-only setter for the demurrage rate: every block a balance sits idle, `bps` of it
+        /// decays away and is burned from the total supply on the account's next write. Idle time
+        /// counts from the later of that write and this rate change, 0 turns the decay off
+        #[ink(message)]
+        pub fn set_demurrage_bps_per_block(&mut self, bps: u16) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if bps > BPS_DENOMINATOR {
+                    return Err(Error::FeeTooHigh);
+                }
+                this.config_changed(
+                    ConfigKey::DemurrageBps,
+                    this.demurrage_bps.into(),
+                    bps.into(),
+                );
+                this.demurrage_bps = bps;
+                this.demurrage_since = this.env().block_number();
+                Ok(())
+            })
+        }
+
+        /// Returns the block `account` may send again from, 0 if it isn't vote-locked
+        #[ink(message)]
+        pub fn vote_lock_of(&self, account: AccountId) -> BlockNumber {
+            self.vote_locks.get(account).unwrap_or_default()
+        }
+
+        /// Owner-only lock keeping `account` from sending until `until_block` while a governance vote
+        /// runs, incoming transfers still arrive. A block already reached lifts the lock
+        #[ink(message)]
+        pub fn lock_for_vote(
+            &mut self,
+            account: AccountId,
+            until_block: BlockNumber,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if until_block <= this.env().block_number() {
+                    this.vote_locks.remove(account);
+                } else {
+                    this.vote_locks.insert(account, &until_block);
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns the mock USD price feed, scaled by `PRICE_PRECISION`
+        #[ink(message)]
+        pub fn price(&self) -> Balance {
+            self.price_usd
+        }
+
+        /// Owner-only setter for the mock price feed, scaled by `PRICE_PRECISION`
+        #[ink(message)]
+        pub fn set_price(&mut self, price: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(ConfigKey::Price, this.price_usd, price);
+                this.price_usd = price;
+                Ok(())
+            })
+        }
+
+        /// Returns `account`'s balance valued at the mock price, in the token's own units of USD
+        #[ink(message)]
+        pub fn value_of(&self, account: AccountId) -> Balance {
+            mul_div(self.balance_of(account), self.price_usd, PRICE_PRECISION)
+                .unwrap_or(Balance::MAX)
+        }
+
+        /// Returns the contracts subscribed to `account`'s balance changes
+        #[ink(message)]
+        pub fn balance_subscribers(&self, account: AccountId) -> Vec<AccountId> {
+            self.balance_subscribers.get(account).unwrap_or_default()
+        }
+
+        /// Returns whether `account` approved `subscriber` to watch its balance
+        #[ink(message)]
+        pub fn is_subscriber_approved(&self, account: AccountId, subscriber: AccountId) -> bool {
+            self.approved_subscribers.contains((account, subscriber))
+        }
+
+        /// Lets `subscriber` subscribe to the caller's balance changes, or takes that back. Taking it
+        /// back also evicts `subscriber` if it already subscribed
+        #[ink(message)]
+        pub fn approve_subscriber(&mut self, subscriber: AccountId, approved: bool) -> Result<()> {
+            self.track(|this| {
+                let account = this.env().caller();
+                if approved {
+                    this.approved_subscribers.insert((account, subscriber), &());
+                } else {
+                    this.approved_subscribers.remove((account, subscriber));
+                    this.drop_subscriber(account, subscriber);
+                }
+                Ok(())
+            })
+        }
+
+        /// Subscribes the calling contract to `account`'s balance changes, it gets
+        /// `on_balance_change(account, new_balance)` after every transfer touching `account`. Only
+        /// subscribers `account` approved can, so nobody can fill its slots against its will
+        #[ink(message)]
+        pub fn subscribe(&mut self, account: AccountId) -> Result<()> {
+            self.track(|this| {
+                let subscriber = this.env().caller();
+                if !this.approved_subscribers.contains((account, subscriber)) {
+                    return Err(Error::SubscriberNotApproved);
+                }
+                let mut subscribers = this.balance_subscribers(account);
+                if subscribers.contains(&subscriber) {
+                    return Ok(());
+                }
+                if subscribers.len() >= MAX_BALANCE_SUBSCRIBERS {
+                    return Err(Error::TooManySubscribers);
+                }
+                subscribers.push(subscriber);
+                this.balance_subscribers.insert(account, &subscribers);
+                Ok(())
+            })
+        }
+
+        /// Drops the calling contract's subscription to `account`'s balance changes
+        #[ink(message)]
+        pub fn unsubscribe(&mut self, account: AccountId) -> Result<()> {
+            self.track(|this| {
+                let subscriber = this.env().caller();
+                this.drop_subscriber(account, subscriber);
+                Ok(())
+            })
+        }
+
+        /// Returns the number of blocks a queued transfer waits before it can be finalized
+        #[ink(message)]
+        pub fn transfer_delay(&self) -> BlockNumber {
+            self.transfer_delay
+        }
+
+        /// Owner-only setter for the transfer queue delay, only requests made afterwards use it
+        #[ink(message)]
+        pub fn set_transfer_delay(&mut self, delay: BlockNumber) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::TransferDelay,
+                    this.transfer_delay.into(),
+                    delay.into(),
+                );
+                this.transfer_delay = delay;
+                Ok(())
+            })
+        }
+
+        /// Returns the queued transfer stored under `id`
+        #[ink(message)]
+        pub fn transfer_request(&self, id: u32) -> Option<TransferRequest> {
+            self.transfer_requests.get(id)
+        }
+
+        /// Takes `amount` out of the caller's balance and queues it for `to`, releasable after
+        /// `transfer_delay` blocks, and returns the request id
+        #[ink(message)]
+        pub fn request_transfer(&mut self, to: AccountId, amount: Balance) -> Result<u32> {
+            self.track(|this| {
+                let from = this.env().caller();
+                let balance = this.balance_of(from);
+                if balance < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+                let id = this.next_transfer_request_id;
+                this.next_transfer_request_id = id.checked_add(1).ok_or(Error::Overflow)?;
+                let release = this
+                    .env()
+                    .block_number()
+                    .checked_add(this.transfer_delay)
+                    .ok_or(Error::Overflow)?;
+
+                this.set_balance(&from, balance - amount);
+                this.total_queued += amount;
+                this.transfer_requests
+                    .insert(id, &(from, to, amount, release));
+
+                this.emit(TransferRequested {
+                    id,
+                    from,
+                    to,
+                    amount,
+                    release,
+                });
+                Ok(id)
+            })
+        }
+
+        /// Completes queued transfer `id` once its release block is reached, callable by anyone. It
+        /// runs as a regular transfer from the sender at that point, fees and limits included
+        #[ink(message)]
+        pub fn finalize_transfer(&mut self, id: u32) -> Result<()> {
+            self.track(|this| {
+                let (from, to, amount, release) = this
+                    .transfer_requests
+                    .get(id)
+                    .ok_or(Error::TransferRequestNotFound)?;
+                if this.env().block_number() < release {
+                    return Err(Error::StillLocked);
+                }
+
+                this.release_queued(id, &from, amount);
+                this.transfer_from_to(&from, &to, amount)
+            })
+        }
+
+        /// Cancels the caller's queued transfer `id` and returns its amount to the caller's balance
+        #[ink(message)]
+        pub fn cancel_transfer(&mut self, id: u32) -> Result<()> {
+            self.track(|this| {
+                let (from, _, amount, _) = this
+                    .transfer_requests
+                    .get(id)
+                    .ok_or(Error::TransferRequestNotFound)?;
+                if from != this.env().caller() {
+                    return Err(Error::NotRequester);
+                }
+
+                this.release_queued(id, &from, amount);
+                this.emit(TransferRequestCancelled { id });
+                Ok(())
+            })
+        }
+
+        /// Returns the tokens held in the contract's internal escrow slots rather than in balances:
+        /// staked, locked or vesting, queued for a delayed transfer, parked in sub-accounts, waiting
+        /// to be claimed as dividends, pending withdrawal as accidental burns and collected fees, or
+        /// backing the reserve
+        #[ink(message)]
+        pub fn escrowed_supply(&self) -> Balance {
+            self.total_staked
+                + self.total_locked
+                + self.total_queued
+                + self.total_in_subs
+                + self.burned_accidentally
+                + self.collected_fees
+                + self.reserve
+                + self.total_vesting
+                + self.total_dividends_unclaimed
+        }
+
+        /// Returns the total supply minus `escrowed_supply`, what the balances hold between them.
+        /// Saturates at 0 should the buckets ever add up past the supply, `check_conservation` flags that
+        #[ink(message)]
+        pub fn liquid_supply(&self) -> Balance {
+            self.total_supply.saturating_sub(self.escrowed_supply())
+        }
+
+        /// Returns the supply actually in circulation, the liquid supply minus the treasury's balance,
+        /// saturating at 0 like `liquid_supply`
+        #[ink(message)]
+        pub fn circulating_supply(&self) -> Balance {
+            let treasury_balance = self
+                .treasury
+                .map_or(0, |treasury| self.balance_of(treasury));
+            self.liquid_supply().saturating_sub(treasury_balance)
+        }
+
+        /// Returns whether the held balances and the escrow buckets of `escrowed_supply` add back up
+        /// to the total supply. Only the running totals are compared, so it stays cheap however many
+        /// holders there are, and `false` means some path let them drift apart
+        #[ink(message)]
+        pub fn check_conservation(&self) -> bool {
+            self.total_held.checked_add(self.escrowed_supply()) == Some(self.total_supply)
+        }
+
+        /// Returns the transfer fees accrued to the contract and not withdrawn yet
+        #[ink(message)]
+        pub fn collected_fees(&self) -> Balance {
+            self.collected_fees
+        }
+
+        /// Owner-only sweep of the fees accrued to the contract, credited to `to`. Fees accrue to the
+        /// contract through a fee split naming its own account, or a tier fee with no splits at all
+        #[ink(message)]
+        pub fn withdraw_fees(&mut self, to: AccountId) -> Result<Balance> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let amount = this.collected_fees;
+                this.collected_fees = 0;
+                let balance = this.balance_of(to);
+                this.set_balance(&to, balance + amount);
+
+                this.emit(Transfer {
+                    from: Some(this.env().account_id()),
+                    to: Some(to),
+                    value: amount,
+                });
+                Ok(amount)
+            })
+        }
+
+        /// Returns whether the transfer fee accrues to the backing reserve
+        #[ink(message)]
+        pub fn fees_to_reserve(&self) -> bool {
+            self.fees_to_reserve
+        }
+
+        /// Owner-only switch sending the whole transfer fee into the backing reserve instead of paying
+        /// it to the fee splits. Reflecting the fee takes precedence while that is on too
+        #[ink(message)]
+        pub fn set_fees_to_reserve(&mut self, fees_to_reserve: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::FeesToReserve,
+                    this.fees_to_reserve.into(),
+                    fees_to_reserve.into(),
+                );
+                this.fees_to_reserve = fees_to_reserve;
+                Ok(())
+            })
+        }
+
+        /// Returns the transfer fees backing `redeem_from_reserve`
+        #[ink(message)]
+        pub fn reserve(&self) -> Balance {
+            self.reserve
+        }
+
+        /// Burns `amount` of the caller's tokens and pays it `reserve * amount / total_supply` out of
+        /// the reserve, returning the payout. The share is taken before the burn shrinks the supply
+        #[ink(message)]
+        pub fn redeem_from_reserve(&mut self, amount: Balance) -> Result<Balance> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                if this.balance_of(caller) < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+                let payout = mul_div(this.reserve, amount, this.total_supply)?;
+                this.burn_from(&caller, amount)?;
+                this.reserve -= payout;
+                let balance = this.balance_of(caller);
+                this.set_balance(&caller, balance + payout);
+
+                this.emit(Transfer {
+                    from: Some(this.env().account_id()),
+                    to: Some(caller),
+                    value: payout,
+                });
+                Ok(payout)
+            })
+        }
+
+        /// Returns the tokens sent to the zero address and not recovered yet
+        #[ink(message)]
+        pub fn accidental_burns(&self) -> Balance {
+            self.burned_accidentally
+        }
+
+        /// Owner-only recovery of everything sent to the zero address, credited to `to`
+        #[ink(message)]
+        pub fn recover_accidental(&mut self, to: AccountId) -> Result<Balance> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let amount = this.burned_accidentally;
+                this.burned_accidentally = 0;
+                let balance = this.balance_of(to);
+                this.set_balance(&to, balance + amount);
+
+                this.emit(Transfer {
+                    from: Some(zero_account()),
+                    to: Some(to),
+                    value: amount,
+                });
+                Ok(amount)
+            })
+        }
+
+        /// Minting of `amount` new tokens to `to` by the owner or a minter, plus the treasury's cut on top
+        #[ink(message)]
+        pub fn mint(&mut self, to: AccountId, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_minter()?;
+                this.mint_with_treasury_cut(&to, amount)
+            })
+        }
+
+        /// Owner-only registration of another mock token under `symbol`, returning its token id. The
+        /// extra tokens are plain ledgers without this token's fees, limits or escrow features
+        #[ink(message)]
+        pub fn register_token(&mut self, symbol: String) -> Result<u32> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let token_id = this.next_token_id;
+                this.next_token_id = token_id.checked_add(1).ok_or(Error::Overflow)?;
+                this.token_symbols.insert(token_id, &symbol);
+                Ok(token_id)
+            })
+        }
+
+        /// Returns the symbol a mock token was registered under, `None` for token 0 and unknown ids
+        #[ink(message)]
+        pub fn token_symbol(&self, token_id: u32) -> Option<String> {
+            self.token_symbols.get(token_id)
+        }
+
+        /// Returns the total supply of token `token_id`, token 0 being `total_supply`
+        #[ink(message)]
+        pub fn total_supply_of(&self, token_id: u32) -> Balance {
+            if token_id == 0 {
+                return self.total_supply();
+            }
+            self.token_supplies.get(token_id).unwrap_or_default()
+        }
+
+        /// Returns `account`'s balance of token `token_id`, token 0 being `balance_of`
+        #[ink(message)]
+        pub fn balance_of_token(&self, token_id: u32, account: AccountId) -> Balance {
+            if token_id == 0 {
+                return self.balance_of(account);
+            }
+            self.token_balances
+                .get((token_id, account))
+                .unwrap_or_default()
+        }
+
+        /// Transfers `amount` of token `token_id` from the caller to `to`, token 0 going through the
+        /// regular `transfer`
+        #[ink(message)]
+        pub fn transfer_token(
+            &mut self,
+            token_id: u32,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                let from = this.env().caller();
+                if token_id == 0 {
+                    return this.transfer_from_to(&from, &to, amount);
+                }
+                this.ensure_token(token_id)?;
+                let from_balance = this.balance_of_token(token_id, from);
+                if from_balance < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+
+                this.token_balances
+                    .insert((token_id, from), &(from_balance - amount));
+                let to_balance = this.balance_of_token(token_id, to);
+                this.token_balances
+                    .insert((token_id, to), &(to_balance + amount));
+
+                this.emit(TokenTransfer {
+                    token_id,
+                    from: Some(from),
+                    to: Some(to),
+                    value: amount,
+                });
+                Ok(())
+            })
+        }
+
+        /// Minting of `amount` of token `token_id` to `to` by the owner or a minter, token 0 going
+        /// through the regular `mint`
+        #[ink(message)]
+        pub fn mint_token(&mut self, token_id: u32, to: AccountId, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_minter()?;
+                if token_id == 0 {
+                    return this.mint_with_treasury_cut(&to, amount);
+                }
+                this.ensure_token(token_id)?;
+                let supply = this
+                    .total_supply_of(token_id)
+                    .checked_add(amount)
+                    .ok_or(Error::Overflow)?;
+
+                this.token_supplies.insert(token_id, &supply);
+                let balance = this.balance_of_token(token_id, to);
+                this.token_balances
+                    .insert((token_id, to), &(balance + amount));
+
+                this.emit(TokenTransfer {
+                    token_id,
+                    from: None,
+                    to: Some(to),
+                    value: amount,
+                });
+                Ok(())
+            })
+        }
+
+        /// Like `mint`, but only once per external `op_id`: a relayer retrying an operation already
+        /// minted for gets `Ok(())` without minting again
+        #[ink(message)]
+        pub fn mint_idempotent(
+            &mut self,
+            op_id: u128,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_minter()?;
+                if this.processed_ops.contains(op_id) {
+                    return Ok(());
+                }
+                this.mint_with_treasury_cut(&to, amount)?;
+                this.processed_ops.insert(op_id, &());
+                Ok(())
+            })
+        }
+
+        /// Returns whether `account` may call `mint`, i.e. it is the owner or a minter and minting
+        /// wasn't renounced
+        #[ink(message)]
+        pub fn can_mint(&self, account: AccountId) -> bool {
+            !self.minting_renounced
+                && self.minting_active()
+                && (account == self.owner || self.minters.contains(account))
+        }
+
+        /// Returns the block minting becomes possible at, `None` if no grace period was set
+        #[ink(message)]
+        pub fn mint_activation_block(&self) -> Option<BlockNumber> {
+            self.mint_activation_block
+        }
+
+        /// Owner-only, one-time setter delaying `mint` and `mint_batch` until the chain reaches
+        /// `block`, for a delayed-inflation launch. Once set it can't be moved
+        #[ink(message)]
+        pub fn set_mint_activation_block(&mut self, block: BlockNumber) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if this.mint_activation_block.is_some() {
+                    return Err(Error::MintActivationFixed);
+                }
+                this.config_changed(ConfigKey::MintActivationBlock, 0, block.into());
+                this.mint_activation_block = Some(block);
+                Ok(())
+            })
+        }
+
+        /// Owner-only setter granting `account` minting or taking it away
+        #[ink(message)]
+        pub fn set_minter(&mut self, account: AccountId, minter: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let was_minter = this.minters.contains(account);
+                if minter {
+                    this.minters.insert(account, &());
+                } else {
+                    this.minters.remove(account);
+                }
+                this.account_setting_changed(
+                    ConfigKey::Minter,
+                    account,
+                    was_minter.into(),
+                    minter.into(),
+                );
+                Ok(())
+            })
+        }
+
+        /// Returns the treasury's cut of every owner mint in basis points
+        #[ink(message)]
+        pub fn treasury_mint_bps(&self) -> u16 {
+            self.treasury_mint_bps
+        }
+
+        /// Owner-only setter for the treasury's mint cut, capped at `MAX_FEE_BPS`. `mint` and
+        /// `mint_batch` mint it to the treasury on top of `amount`, bridge mints don't pay it
+        #[ink(message)]
+        pub fn set_treasury_mint_bps(&mut self, treasury_mint_bps: u16) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if treasury_mint_bps > MAX_FEE_BPS {
+                    return Err(Error::FeeTooHigh);
+                }
+                this.config_changed(
+                    ConfigKey::TreasuryMintBps,
+                    this.treasury_mint_bps.into(),
+                    treasury_mint_bps.into(),
+                );
+                this.treasury_mint_bps = treasury_mint_bps;
+                Ok(())
+            })
+        }
+
+        /// Returns whether minting was renounced for good
+        #[ink(message)]
+        pub fn minting_renounced(&self) -> bool {
+            self.minting_renounced
+        }
+
+        /// Owner-only kill-switch permanently disabling `mint`, `mint_batch` and `bridge_mint`, every
+        /// path going through `mint_to`. There is no way to undo it
+        #[ink(message)]
+        pub fn renounce_minting(&mut self) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::MintingRenounced,
+                    this.minting_renounced.into(),
+                    1,
+                );
+                this.minting_renounced = true;
+                Ok(())
+            })
+        }
+
+        /// Burns `amount` of the caller's own tokens, reducing the total supply
+        #[ink(message)]
+        pub fn burn(&mut self, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                this.burn_from(&caller, amount)
+            })
+        }
+
+        /// Returns the tokens minted per unit of native currency swapped in
+        #[ink(message)]
+        pub fn native_peg(&self) -> Balance {
+            self.native_peg
+        }
+
+        /// Owner-only setter for the native peg, which can't be zero
+        #[ink(message)]
+        pub fn set_native_peg(&mut self, native_peg: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if native_peg == 0 {
+                    return Err(Error::InvalidPeg);
+                }
+                this.config_changed(ConfigKey::NativePeg, this.native_peg, native_peg);
+                this.native_peg = native_peg;
+                Ok(())
+            })
+        }
+
+        /// Returns the stabilizer parameters as `(reserve, reserve_ratio_bps, step_bps)`
+        #[ink(message)]
+        pub fn stabilizer(&self) -> Option<(Balance, u16, u16)> {
+            self.stabilizer
+        }
+
+        /// Returns the total supply the stabilizer steers toward, the reserve divided by the reserve
+        /// ratio, if it is on
+        #[ink(message)]
+        pub fn stabilizer_target(&self) -> Option<Balance> {
+            let (reserve, reserve_ratio_bps, _) = self.stabilizer?;
+            Some(
+                mul_div(reserve, BPS_DENOMINATOR.into(), reserve_ratio_bps.into())
+                    .unwrap_or(Balance::MAX),
+            )
+        }
+
+        /// Owner-only setter for the mock stabilizer: each `stabilize` closes `step_bps` of the gap
+        /// between the total supply and `reserve * 10_000 / reserve_ratio_bps`. The ratio can't be
+        /// zero and the step is at most 100%, `None` turns it off
+        #[ink(message)]
+        pub fn set_stabilizer(&mut self, stabilizer: Option<(Balance, u16, u16)>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if let Some((_, reserve_ratio_bps, step_bps)) = stabilizer {
+                    if reserve_ratio_bps == 0 || step_bps > BPS_DENOMINATOR {
+                        return Err(Error::InvalidPeg);
+                    }
+                }
+                // each parameter is reported on its own key, all 0 while the stabilizer is off
+                let (old_reserve, old_ratio, old_step) = this.stabilizer.unwrap_or_default();
+                let (reserve, ratio, step) = stabilizer.unwrap_or_default();
+                this.config_changed(ConfigKey::StabilizerReserve, old_reserve, reserve);
+                this.config_changed(ConfigKey::StabilizerRatio, old_ratio.into(), ratio.into());
+                this.config_changed(ConfigKey::StabilizerStep, old_step.into(), step.into());
+                this.stabilizer = stabilizer;
+                Ok(())
+            })
+        }
+
+        /// Nudges the total supply toward the stabilizer target, callable by anyone. Below it the
+        /// difference is minted to the treasury (or the owner without one), above it burned from the
+        /// same account as far as its balance goes
+        #[ink(message)]
+        pub fn stabilize(&mut self) -> Result<()> {
+            self.track(|this| {
+                let (Some(target), Some((_, _, step_bps))) =
+                    (this.stabilizer_target(), this.stabilizer)
+                else {
+                    return Ok(());
+                };
+                let account = this.treasury.unwrap_or(this.owner);
+                let (mut minted, mut burned) = (0, 0);
+                if target > this.total_supply {
+                    minted = portion(target - this.total_supply, step_bps, BPS_DENOMINATOR);
+                    this.mint_to(&account, minted)?;
+                } else {
+                    burned = portion(this.total_supply - target, step_bps, BPS_DENOMINATOR)
+                        .min(this.balance_of(account));
+                    this.burn_from(&account, burned)?;
+                }
+
+                this.emit(Stabilized { minted, burned });
+                Ok(())
+            })
+        }
+
+        /// Mints the caller `native_peg` tokens per unit of native currency sent along, returning how
+        /// many. The native currency stays in the contract as the reserve paying swaps back out
+        #[ink(message, payable)]
+        pub fn swap_native_for_dai(&mut self) -> Result<Balance> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                let amount = this
+                    .env()
+                    .transferred_value()
+                    .checked_mul(this.native_peg)
+                    .ok_or(Error::Overflow)?;
+                this.mint_to(&caller, amount)?;
+                Ok(amount)
+            })
+        }
+
+        /// Burns `amount` of the caller's tokens and pays out the native currency they peg to from the
+        /// reserve, returning how much. Only whole pegged units are burned, the remainder stays put
+        #[ink(message)]
+        pub fn swap_dai_for_native(&mut self, amount: Balance) -> Result<Balance> {
+            self.track(|this| {
+                let caller = this.env().caller();
+                if this.withdraw_gated && !this.withdraw_whitelist.contains(caller) {
+                    return Err(Error::NotWhitelisted);
+                }
+                let native = amount / this.native_peg;
+                if this.env().balance() < native {
+                    return Err(Error::InsufficientReserve);
+                }
+
+                this.burn_from(&caller, native * this.native_peg)?;
+                this.env()
+                    .transfer(caller, native)
+                    .map_err(|_| Error::InsufficientReserve)?;
+                Ok(native)
+            })
+        }
+
+        /// Returns whether `swap_dai_for_native` is limited to whitelisted accounts
+        #[ink(message)]
+        pub fn withdraw_gated(&self) -> bool {
+            self.withdraw_gated
+        }
+
+        /// Owner-only toggle limiting `swap_dai_for_native` to the withdrawal whitelist, swapping in
+        /// stays open to everyone
+        #[ink(message)]
+        pub fn set_withdraw_gated(&mut self, withdraw_gated: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::WithdrawGated,
+                    this.withdraw_gated.into(),
+                    withdraw_gated.into(),
+                );
+                this.withdraw_gated = withdraw_gated;
+                Ok(())
+            })
+        }
+
+        /// Returns whether `account` may swap back out to native currency while withdrawals are gated
+        #[ink(message)]
+        pub fn is_withdraw_whitelisted(&self, account: AccountId) -> bool {
+            self.withdraw_whitelist.contains(account)
+        }
+
+        /// Owner-only setter adding `account` to or removing it from the withdrawal whitelist
+        #[ink(message)]
+        pub fn set_withdraw_whitelisted(
+            &mut self,
+            account: AccountId,
+            whitelisted: bool,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if whitelisted {
+                    this.withdraw_whitelist.insert(account, &());
+                } else {
+                    this.withdraw_whitelist.remove(account);
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns whether `approve_and_call` and its variants refuse to be reentered from a callback
+        #[ink(message)]
+        pub fn reentrancy_guard_enabled(&self) -> bool {
+            self.reentrancy_guard_enabled
+        }
+
+        /// Owner-only toggle for the reentrancy guard. TEST-ONLY FOOTGUN: switching it off lets a
+        /// callback reenter `approve_and_call`, which no real deployment should allow. It exists so
+        /// consumers can check how they cope with a reentrant token
+        #[ink(message)]
+        pub fn set_reentrancy_guard_enabled(&mut self, enabled: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::ReentrancyGuard,
+                    this.reentrancy_guard_enabled.into(),
+                    enabled.into(),
+                );
+                this.reentrancy_guard_enabled = enabled;
+                Ok(())
+            })
+        }
+
+        /// Returns whether holders can transfer their tokens
+        #[ink(message)]
+        pub fn transferable(&self) -> bool {
+            self.transferable
+        }
+
+        /// Owner-only toggle for soulbound mode, `transfer` and `transfer_from` revert while it's off
+        #[ink(message)]
+        pub fn set_transferable(&mut self, transferable: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::Transferable,
+                    this.transferable.into(),
+                    transferable.into(),
+                );
+                this.transferable = transferable;
+                Ok(())
+            })
+        }
+
+        /// Approves `spender` for `amount` then calls its `receive_approval(owner, amount, data)` in the
+        /// same transaction, so it can pull the tokens right away. Reverts both if the callback fails
+        #[ink(message)]
+        pub fn approve_and_call(
+            &mut self,
+            spender: AccountId,
+            amount: Balance,
+            data: Vec<u8>,
+        ) -> Result<()> {
+            self.approve_and_call_with_selector(spender, amount, RECEIVE_APPROVAL_SELECTOR, data)
+        }
+
+        /// `approve_and_call` against any receiver interface, invoking the message at `selector` on
+        /// `spender` with `(owner, amount, data)` as its arguments
+        #[ink(message)]
+        pub fn approve_and_call_with_selector(
+            &mut self,
+            spender: AccountId,
+            amount: Balance,
+            selector: [u8; 4],
+            data: Vec<u8>,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_not_reentered()?;
+                let owner = this.env().caller();
+                this.approve_from(owner, spender, amount)?;
+
+                let called = this.invoke_reentrant(
+                    spender,
+                    ExecutionInput::new(Selector::new(selector))
+                        .push_arg(owner)
+                        .push_arg(amount)
+                        .push_arg(data),
+                );
+                if !called {
+                    return Err(Error::CallbackFailed);
+                }
+                Ok(())
+            })
+        }
+
+        /// Transfers from the caller to every `(to, amount)` entry, all or nothing, at most `MAX_BATCH` entries.
+        /// Entries run strictly in index order, each one seeing the balances the earlier ones left,
+        /// so repeated recipients simply accumulate
+        #[ink(message)]
+        pub fn batch_transfer(&mut self, transfers: Vec<(AccountId, Balance)>) -> Result<()> {
+            self.track(|this| {
+                ensure_batch(transfers.len())?;
+                // every entry is sent by the caller
+                if transfers.len() > this.max_transfers_per_tx as usize {
+                    return Err(Error::TooManyTransfers);
+                }
+                let sender = this.env().caller();
+                for (to, amount) in transfers {
+                    this.transfer_from_to(&sender, &to, amount)?;
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns the most transfers one sender may make in a single batch
+        #[ink(message)]
+        pub fn max_transfers_per_tx(&self) -> u32 {
+            self.max_transfers_per_tx
+        }
+
+        /// Owner-only setter throttling how many transfers one sender can pack into a batch, below
+        /// `MAX_BATCH`. `u32::MAX` disables it
+        #[ink(message)]
+        pub fn set_max_transfers_per_tx(&mut self, max_transfers: u32) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::MaxTransfersPerTx,
+                    this.max_transfers_per_tx.into(),
+                    max_transfers.into(),
+                );
+                this.max_transfers_per_tx = max_transfers;
+                Ok(())
+            })
+        }
+
+        /// Approves every `(spender, amount)` entry for the caller, at most `MAX_BATCH` entries
+        #[ink(message)]
+        pub fn batch_approve(&mut self, approvals: Vec<(AccountId, Balance)>) -> Result<()> {
+            self.track(|this| {
+                ensure_batch(approvals.len())?;
+                let owner = this.env().caller();
+                for (spender, amount) in approvals {
+                    this.approve_from(owner, spender, amount)?;
+                }
+                Ok(())
+            })
+        }
+
+        /// Minting to every `(to, amount)` entry by the owner or a minter, at most `MAX_BATCH` entries
+        #[ink(message)]
+        pub fn mint_batch(&mut self, mints: Vec<(AccountId, Balance)>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_minter()?;
+                ensure_batch(mints.len())?;
+                for (to, amount) in mints {
+                    this.mint_with_treasury_cut(&to, amount)?;
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns the name of the token
+        #[ink(message)]
+        pub fn name(&self) -> String {
+            String::from(TOKEN_NAME)
+        }
+
+        /// Returns the symbol of the token
+        #[ink(message)]
+        pub fn symbol(&self) -> String {
+            String::from(TOKEN_SYMBOL)
+        }
+
+        /// Returns the number of decimals the balances are denominated in
+        #[ink(message)]
+        pub fn decimals(&self) -> u8 {
+            self.decimals
+        }
+
+        /// Owner-only redenomination to `new_decimals`, multiplying every balance and the total supply
+        /// by `factor`. Balances can't be enumerated, so each one is scaled lazily the next time it's
+        /// read or written. Dividing (`multiply == false`) is rejected as precision loss: without
+        /// enumerating the balances there's no telling whether one of them would round down. The
+        /// escrow records aren't scaled, so it's rejected while `escrowed_supply` is non-zero.
+        /// Allowances and snapshots keep their face value
+        #[ink(message)]
+        pub fn redenominate(
+            &mut self,
+            new_decimals: u8,
+            factor: Balance,
+            multiply: bool,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if this.escrowed_supply() != 0 {
+                    return Err(Error::EscrowOutstanding);
+                }
+                if factor == 0 || !multiply {
+                    return Err(Error::Overflow);
+                }
+                let total_supply = this
+                    .total_supply
+                    .checked_mul(factor)
+                    .ok_or(Error::Overflow)?;
+
+                // every balance is at most the total supply, so none of them can overflow either
+                this.set_total_supply(total_supply);
+                this.total_held *= factor;
+                this.redenominations.push((factor, multiply));
+                this.decimals = new_decimals;
+
+                this.emit(Redenominated {
+                    decimals: new_decimals,
+                    factor,
+                    multiply,
+                });
+                Ok(())
+            })
+        }
+
+        /// Returns the block `merchant` last pulled a subscription payment from `owner` at
+        #[ink(message)]
+        pub fn last_pull_block(
+            &self,
+            owner: AccountId,
+            merchant: AccountId,
+        ) -> Option<BlockNumber> {
+            self.last_pulls.get((owner, merchant))
+        }
+
+        /// Metered subscription billing, the calling merchant pulls `amount` from `owner` at most once
+        /// every `interval_blocks`. Spends the merchant's allowance just like `transfer_from`
+        #[ink(message)]
+        pub fn pull_subscription(
+            &mut self,
+            owner: AccountId,
+            amount: Balance,
+            interval_blocks: BlockNumber,
+        ) -> Result<()> {
+            self.track(|this| {
+                let merchant = this.env().caller();
+                let now = this.env().block_number();
+                if let Some(last_pull) = this.last_pulls.get((owner, merchant)) {
+                    if now < last_pull.saturating_add(interval_blocks) {
+                        return Err(Error::TransferCooldown);
+                    }
+                }
+
+                this.transfer_from_as(merchant, &owner, &merchant, amount)?;
+                this.last_pulls.insert((owner, merchant), &now);
+                Ok(())
+            })
+        }
+
+        /// Returns the subscription stored under `id`
+        #[ink(message)]
+        pub fn subscription(&self, id: u32) -> Option<Subscription> {
+            self.subscriptions.get(id)
+        }
+
+        /// Schedules `count` payments of `amount` from the caller to `to`, one every `interval` blocks
+        /// with the first due right away, and returns the subscription id. Each installment spends
+        /// the caller's allowance for `to`, so revoking it stops the schedule
+        #[ink(message)]
+        pub fn create_subscription(
+            &mut self,
+            to: AccountId,
+            amount: Balance,
+            interval: BlockNumber,
+            count: u32,
+        ) -> Result<u32> {
+            self.track(|this| {
+                let payer = this.env().caller();
+                let id = this.next_subscription_id;
+                this.next_subscription_id = id.checked_add(1).ok_or(Error::Overflow)?;
+                let due = this.env().block_number();
+                this.subscriptions
+                    .insert(id, &(payer, to, amount, interval, due, count, 0));
+                Ok(id)
+            })
+        }
+
+        /// Pulls the next installment of subscription `id` once it's due, callable by anyone
+        #[ink(message)]
+        pub fn execute_subscription(&mut self, id: u32) -> Result<()> {
+            self.track(|this| {
+                let (payer, to, amount, interval, due, count, executed) = this
+                    .subscriptions
+                    .get(id)
+                    .ok_or(Error::SubscriptionNotFound)?;
+                if executed >= count {
+                    return Err(Error::SubscriptionEnded);
+                }
+                let next_due = interval
+                    .checked_mul(executed)
+                    .and_then(|offset| due.checked_add(offset))
+                    .ok_or(Error::Overflow)?;
+                if this.env().block_number() < next_due {
+                    return Err(Error::NotDue);
+                }
+
+                this.transfer_from_as(to, &payer, &to, amount)?;
+                this.subscriptions
+                    .insert(id, &(payer, to, amount, interval, due, count, executed + 1));
+                Ok(())
+            })
+        }
+
+        /// Returns the contract version and whether the deployment was initialized, for upgrade tooling
+        #[ink(message)]
+        pub fn version(&self) -> (u16, bool) {
+            (CONTRACT_VERSION, self.initialized)
+        }
+
+        /// Owner-only, one-way switch marking the deployment as initialized
+        #[ink(message)]
+        pub fn initialize(&mut self) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if this.initialized {
+                    return Err(Error::AlreadyInitialized);
+                }
+                this.initialized = true;
+                Ok(())
+            })
+        }
+
+        /// Owner-only bulk load of `(owner, spender, amount)` allowances from a previous deployment,
+        /// firing an `Approval` for each. Only possible until `initialize` locks the deployment, and
+        /// the allowance caps aren't applied to the migrated state
+        #[ink(message)]
+        pub fn import_allowances(
+            &mut self,
+            entries: Vec<(AccountId, AccountId, Balance)>,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                if this.initialized {
+                    return Err(Error::AlreadyInitialized);
+                }
+                ensure_batch(entries.len())?;
+                for (owner, spender, amount) in entries {
+                    this.allowance_uses.remove((owner, spender));
+                    this.allowance_refills.remove((owner, spender));
+                    this.allowance_recipients.remove((owner, spender));
+                    this.write_allowance(owner, spender, amount);
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns whether transfers from or to `account` skip the fees
+        #[ink(message)]
+        pub fn is_fee_exempt(&self, account: AccountId) -> bool {
+            self.fee_exempt.contains(account)
+        }
+
+        /// Owner-only setter adding `account` to or removing it from the fee exemption list
+        #[ink(message)]
+        pub fn set_fee_exempt(&mut self, account: AccountId, exempt: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                let was_exempt = this.fee_exempt.contains(account);
+                if exempt {
+                    this.fee_exempt.insert(account, &());
+                } else {
+                    this.fee_exempt.remove(account);
+                }
+                this.account_setting_changed(
+                    ConfigKey::FeeExempt,
+                    account,
+                    was_exempt.into(),
+                    exempt.into(),
+                );
+                Ok(())
+            })
+        }
+
+        /// Returns the trusted bridge account, if one is configured
+        #[ink(message)]
+        pub fn bridge(&self) -> Option<AccountId> {
+            self.bridge
+        }
+
+        /// Owner-only setter for the trusted bridge account
+        #[ink(message)]
+        pub fn set_bridge(&mut self, bridge: Option<AccountId>) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.address_changed(ConfigKey::Bridge, this.bridge, bridge);
+                this.bridge = bridge;
+                Ok(())
+            })
+        }
+
+        /// Returns whether the bridge message with `src_nonce` was already minted
+        #[ink(message)]
+        pub fn is_nonce_processed(&self, src_nonce: u128) -> bool {
+            self.processed_nonces.contains(src_nonce)
+        }
+
+        /// Bridge-only minting of tokens that arrived from another chain. Each source-chain message
+        /// `src_nonce` is minted once, replays fail with `Error::NonceAlreadyProcessed`
+        #[ink(message)]
+        pub fn bridge_mint(
+            &mut self,
+            to: AccountId,
+            amount: Balance,
+            src_nonce: u128,
+        ) -> Result<()> {
+            self.track(|this| {
+                this.ensure_bridge()?;
+                if this.processed_nonces.contains(src_nonce) {
+                    return Err(Error::NonceAlreadyProcessed);
+                }
+                this.processed_nonces.insert(src_nonce, &());
+                this.mint_to(&to, amount)?;
+
+                this.bridge_nonce += 1;
+                this.emit(BridgeMint {
+                    to,
+                    amount,
+                    nonce: this.bridge_nonce,
+                    src_nonce,
+                });
+                Ok(())
+            })
+        }
+
+        /// Bridge-only burning of tokens leaving for another chain
+        #[ink(message)]
+        pub fn bridge_burn(&mut self, from: AccountId, amount: Balance) -> Result<()> {
+            self.track(|this| {
+                this.ensure_bridge()?;
+                this.burn_from(&from, amount)?;
+
+                this.bridge_nonce += 1;
+                this.emit(BridgeBurn {
+                    from,
+                    amount,
+                    nonce: this.bridge_nonce,
+                });
+                Ok(())
+            })
+        }
+
+        /// Returns the last `RECENT_TRANSFERS_CAP` transfers `account` sent or received as
+        /// `(counterparty, amount received, block)`, oldest first
+        #[ink(message)]
+        pub fn recent_transfers_of(&self, account: AccountId) -> TransferHistory {
+            self.recent_transfers.get(account).unwrap_or_default()
+        }
+
+        /// Returns the number of accounts holding a nonzero balance
+        #[ink(message)]
+        pub fn holder_count(&self) -> u32 {
+            self.holder_count
+        }
+
+        /// Returns the number of accounts that ever held tokens, drained ones included
+        #[ink(message)]
+        pub fn ever_holders(&self) -> u32 {
+            self.ever_holders
+        }
+
+        /// Returns every `HOLDER_BUCKETS` threshold with the number of holders from it up to the next one.
+        /// Only an approximation: balances grown or shrunk by reflection or redenomination are
+        /// counted in the range they were last written in
+        #[ink(message)]
+        pub fn holder_distribution(&self) -> Vec<(u128, u32)> {
+            HOLDER_BUCKETS
+                .iter()
+                .copied()
+                .zip(self.holder_buckets.iter().copied())
+                .collect()
+        }
+
+        /// Returns the most distinct holders allowed
+        #[ink(message)]
+        pub fn max_holders(&self) -> u32 {
+            self.max_holders
+        }
+
+        /// Owner-only setter for the holder cap, transfers and mints creating a holder past it are
+        /// rejected while ones between existing holders are unaffected. `u32::MAX` disables it
+        #[ink(message)]
+        pub fn set_max_holders(&mut self, max_holders: u32) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::MaxHolders,
+                    this.max_holders.into(),
+                    max_holders.into(),
+                );
+                this.max_holders = max_holders;
+                Ok(())
+            })
+        }
+
+        /// Returns the number of spenders `owner` currently has a non-zero allowance for
+        #[ink(message)]
+        pub fn approval_count(&self, owner: AccountId) -> u32 {
+            self.approval_counts.get(owner).unwrap_or_default()
+        }
+
+        /// Returns the most spenders an owner may have approved at once
+        #[ink(message)]
+        pub fn max_approvals_per_owner(&self) -> u32 {
+            self.max_approvals_per_owner
+        }
+
+        /// Owner-only setter bounding the allowance entries any one owner can create, against storage
+        /// bloat. Changing an existing approval or revoking one is always allowed, `u32::MAX` disables it
+        #[ink(message)]
+        pub fn set_max_approvals_per_owner(&mut self, max_approvals: u32) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::MaxApprovalsPerOwner,
+                    this.max_approvals_per_owner.into(),
+                    max_approvals.into(),
+                );
+                this.max_approvals_per_owner = max_approvals;
+                Ok(())
+            })
+        }
+
+        /// Returns every enforced limit at once, for dashboards reading the whole configuration
+        #[ink(message)]
+        pub fn limits(&self) -> Limits {
+            Limits {
+                cap: self.supply_cap,
+                max_transfer: self.max_transfer,
+                min_transfer: self.min_transfer,
+                daily_limit: self.daily_limit,
+                limit_window: self.limit_window,
+                min_balance: self.min_balance,
+                transfer_fee_bps: self.transfer_fee_bps,
+                burn_fee_bps: self.burn_fee_bps,
+                max_allowance: self.max_allowance,
+                max_holders: self.max_holders,
+                max_transfers_per_tx: self.max_transfers_per_tx,
+                launch_cooldown: self.launch_cooldown,
+                transfer_delay: self.transfer_delay,
+            }
+        }
+
+        /// Private wrapper every mutating message runs its body through, clearing the last error reason
+        /// and recording the new one if the body fails
+        fn track<T>(&mut self, body: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+            self.last_error_reason = None;
+            let result = body(self);
+            if let Err(error) = &result {
+                self.last_error_reason = Some(String::from(error.reason()));
+            }
+            result
+        }
+
+        /// Private guard for the snapshot getters
+        fn ensure_snapshot(&self, snapshot_id: u32) -> Result<()> {
+            if snapshot_id == 0 || snapshot_id > self.snapshot_id {
+                return Err(Error::InvalidSnapshot);
+            }
+            Ok(())
+        }
+
+        /// Private setter every balance change goes through, recording the old balance for the
+        /// latest snapshot first if this is the account's first change since it was taken
+        fn set_balance(&mut self, account: &AccountId, balance: Balance) {
+            // settle the idle decay first as a burn of its own, so the bookkeeping below starts from
+            // the decayed balance `balance` was worked out from
+            let undecayed = self.undecayed_balance(*account);
+            let decayed = self.demurrage_on(account, undecayed);
+            if decayed > 0 {
+                self.last_touch.insert(account, &self.env().block_number());
+                self.set_balance(account, undecayed - decayed);
+                self.set_total_supply(self.total_supply - decayed);
+                self.emit(Burn {
+                    from: *account,
+                    value: decayed,
+                });
+            }
+            // the holder counts took the account in at its last written balance, whatever
+            // redenominations and reflections made of it since. A legacy balance was counted by the
+            // pre-upgrade code already, so it is the one an unmigrated account is counted at
+            let counted = self
+                .balances
+                .get(account)
+                .or_else(|| self.legacy_balances.get(account))
+                .unwrap_or_default();
+            // read before migrating, the legacy entry is what `previous` still reads through
+            let previous = self.balance_of(*account);
+            if self.legacy_balances.take(account).is_some() {
+                self.migrated.insert(account, &());
+            }
+            if self.snapshot_id > 0 {
+                let mut snapshots = self.account_snapshots.get(account).unwrap_or_default();
+                if record_checkpoint(&mut snapshots, self.snapshot_id, previous) {
+                    self.account_snapshots.insert(account, &snapshots);
+                }
+            }
+            match (counted, balance) {
+                (0, 1..) => {
+                    self.holder_count += 1;
+                    if !self.has_received.contains(account) {
+                        self.has_received.insert(account, &());
+                        self.ever_holders = self.ever_holders.saturating_add(1);
+                        self.emit(FirstReceipt {
+                            account: *account,
+                            block: self.env().block_number(),
+                        });
+                    }
+                }
+                (1.., 0) => self.holder_count -= 1,
+                _ => {}
+            }
+            let (from_bucket, to_bucket) = (bucket_of(counted), bucket_of(balance));
+            if from_bucket != to_bucket {
+                if let Some(bucket) = from_bucket {
+                    self.holder_buckets[bucket] -= 1;
+                }
+                if let Some(bucket) = to_bucket {
+                    self.holder_buckets[bucket] += 1;
+                }
+            }
+            self.total_held = self.total_held - previous + balance;
+            self.balances.insert(account, &balance);
+            if self.reflection_index != REFLECTION_PRECISION {
+                self.reflection_marks
+                    .insert(account, &self.reflection_index);
+            }
+            if !self.redenominations.is_empty() {
+                self.balance_epochs
+                    .insert(account, &(self.redenominations.len() as u32));
+            }
+            if self.demurrage_bps > 0 {
+                self.last_touch.insert(account, &self.env().block_number());
+            }
+        }
+
+        /// Private balance read behind `balance_of`, caught up with redenominations and reflections
+        /// but not with the demurrage decay
+        fn undecayed_balance(&self, account: AccountId) -> Balance {
+            // an account not migrated yet reads through to its legacy entry
+            let balance = match self.balances.get(account) {
+                Some(balance) => balance,
+                None => self.legacy_balances.get(account).unwrap_or_default(),
+            };
+            // catch the stored balance up with the redenominations since it was written
+            let epoch = self.balance_epochs.get(account).unwrap_or_default() as usize;
+            let balance = self.redenominations[epoch..].iter().fold(
+                balance,
+                |balance, (factor, multiply)| {
+                    if *multiply {
+                        balance * factor
+                    } else {
+                        balance / factor
+                    }
+                },
+            );
+            // and with its share of the fees reflected since
+            let mark = self
+                .reflection_marks
+                .get(account)
+                .unwrap_or(REFLECTION_PRECISION);
+            if mark == self.reflection_index {
+                return balance;
+            }
+            // a reflected balance is bounded by the held total, so this only saturates if that breaks
+            mul_div(balance, self.reflection_index, mark).unwrap_or(Balance::MAX)
+        }
+
+        /// Private share of `balance` that decayed while `account` sat idle, all of it at most
+        fn demurrage_on(&self, account: &AccountId, balance: Balance) -> Balance {
+            if self.demurrage_bps == 0 || balance == 0 {
+                return 0;
+            }
+            let touched = self
+                .last_touch
+                .get(account)
+                .unwrap_or_default()
+                .max(self.demurrage_since);
+            let idle = self.env().block_number().saturating_sub(touched);
+            let rate = Balance::from(self.demurrage_bps) * Balance::from(idle);
+            mul_div(balance, rate, BPS_DENOMINATOR.into())
+                .unwrap_or(Balance::MAX)
+                .min(balance)
+        }
+
+        /// Private setter every total supply change goes through, snapshotted like `set_balance`
+        fn set_total_supply(&mut self, total_supply: Balance) {
+            if self.snapshot_id > 0 {
+                record_checkpoint(
+                    &mut self.total_supply_snapshots,
+                    self.snapshot_id,
+                    self.total_supply,
+                );
+            }
+            self.total_supply = total_supply;
+        }
+
+        /// Private guard for the holder cap, fails if crediting `amount` would turn `to` into a new
+        /// holder while the cap is already reached
+        fn ensure_holder_room(&self, to: &AccountId, amount: Balance) -> Result<()> {
+            if amount > 0 && self.balance_of(*to) == 0 && self.holder_count >= self.max_holders {
+                return Err(Error::HolderCapReached);
+            }
+            Ok(())
+        }
+
+        /// Private function creating `amount` new tokens for `to` and firing the `Transfer` from `None`
+        fn mint_to(&mut self, to: &AccountId, amount: Balance) -> Result<()> {
+            if self.minting_renounced {
+                return Err(Error::MintingRenounced);
+            }
+            let total_supply = self
+                .total_supply
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            if self.supply_cap > 0 && total_supply > self.supply_cap {
+                return Err(Error::SupplyCapExceeded);
+            }
+            self.ensure_holder_room(to, amount)?;
+
+            self.set_total_supply(total_supply);
+            let balance = self.balance_of(*to);
+            self.set_balance(to, balance + amount);
+
+            self.emit(Transfer {
+                from: None,
+                to: Some(*to),
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Private function behind the owner mints, minting `amount` to `to` and the treasury's cut of
+        /// it to the treasury, if one is configured
+        fn mint_with_treasury_cut(&mut self, to: &AccountId, amount: Balance) -> Result<()> {
+            self.mint_to(to, amount)?;
+            let cut = portion(amount, self.treasury_mint_bps, BPS_DENOMINATOR);
+            match self.treasury {
+                Some(treasury) if cut > 0 => self.mint_to(&treasury, cut),
+                _ => Ok(()),
+            }
+        }
+
+        /// Private function destroying `amount` of `from`'s tokens and firing the `Burn`
+        fn burn_from(&mut self, from: &AccountId, amount: Balance) -> Result<()> {
+            let balance = self.balance_of(*from);
+            if balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+
+            self.set_balance(from, balance - amount);
+            self.set_total_supply(self.total_supply - amount);
+
+            self.emit(Burn {
+                from: *from,
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Private function behind the sub-account messages, moving `amount` of `account`'s tokens
+        /// between its liquid balance (`None`) and its sub-accounts
+        fn move_sub(
+            &mut self,
+            account: AccountId,
+            from_sub: Option<u32>,
+            to_sub: Option<u32>,
+            amount: Balance,
+        ) -> Result<()> {
+            match from_sub {
+                Some(sub_id) => {
+                    let sub_balance = self.sub_balance_of(account, sub_id);
+                    if sub_balance < amount {
+                        return Err(Error::InsufficientBalance);
+                    }
+                    self.sub_balances
+                        .insert((account, sub_id), &(sub_balance - amount));
+                    self.total_in_subs -= amount;
+                }
+                None => {
+                    let balance = self.balance_of(account);
+                    if balance < amount {
+                        return Err(Error::InsufficientBalance);
+                    }
+                    self.set_balance(&account, balance - amount);
+                }
+            }
+            match to_sub {
+                Some(sub_id) => {
+                    let sub_balance = self.sub_balance_of(account, sub_id);
+                    self.sub_balances
+                        .insert((account, sub_id), &(sub_balance + amount));
+                    self.total_in_subs += amount;
+                }
+                None => {
+                    let balance = self.balance_of(account);
+                    self.set_balance(&account, balance + amount);
+                }
+            }
+
+            self.emit(SubAccountMoved {
+                account,
+                from_sub,
+                to_sub,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Private function accruing a fee to the contract itself instead of crediting its balance
+        fn collect_fee(&mut self, from: &AccountId, fee: Balance) {
+            let balance = self.balance_of(*from);
+            self.set_balance(from, balance - fee);
+            self.collected_fees += fee;
+
+            self.emit(Transfer {
+                from: Some(*from),
+                to: Some(self.env().account_id()),
+                value: fee,
+            });
+        }
+
+        /// Private function moving a transfer fee out of `from`'s balance into the backing reserve
+        fn accrue_reserve(&mut self, from: &AccountId, fee: Balance) {
+            if fee == 0 {
+                return;
+            }
+            let balance = self.balance_of(*from);
+            self.set_balance(from, balance - fee);
+            self.reserve += fee;
+
+            self.emit(Transfer {
+                from: Some(*from),
+                to: Some(self.env().account_id()),
+                value: fee,
+            });
+        }
+
+        /// Private function behind transfers to the zero address, holding `amount` aside for the owner to
+        /// recover instead of crediting an account nobody controls. No fees are taken
+        fn hold_accidental(&mut self, from: &AccountId, amount: Balance) -> Result<()> {
+            let balance = self.balance_of(*from);
+            self.set_balance(from, balance - amount);
+            self.burned_accidentally += amount;
+
+            self.emit(Transfer {
+                from: Some(*from),
+                to: Some(zero_account()),
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Private owner-only switch behind `pause`, `unpause` and `set_transfers_paused`
+        fn set_paused(&mut self, paused: bool) -> Result<()> {
+            self.track(|this| {
+                this.ensure_owner()?;
+                this.config_changed(
+                    ConfigKey::Paused,
+                    this.transfers_paused.into(),
+                    paused.into(),
+                );
+                this.transfers_paused = paused;
+                if !paused {
+                    this.outflow.1 = 0;
+                }
+                Ok(())
+            })
+        }
+
+        /// Private function every event goes through, a test run suppressing events skips them.
+        /// Builds outside tests always emit
+        fn emit<E>(&self, event: E)
+        where
+            E: Into<<MockDai as ink::reflect::ContractEventBase>::Type>,
+        {
+            #[cfg(test)]
+            if SUPPRESS_EVENTS.get() {
+                return;
+            }
+            self.env().emit_event(event);
+        }
+
+        /// Owner-only, test-only switch skipping every event for the rest of the test, for loops
+        /// where recording them slows things down. Balances still update as usual
+        #[cfg(test)]
+        fn set_suppress_events(&mut self, suppress: bool) -> Result<()> {
+            self.ensure_owner()?;
+            SUPPRESS_EVENTS.set(suppress);
+            Ok(())
+        }
+
+        /// Private function firing the `ConfigChanged` audit event for an owner setter
+        fn config_changed(&self, key: ConfigKey, old_value: u128, new_value: u128) {
+            self.emit(ConfigChanged {
+                key: key as u8,
+                old_value,
+                new_value,
+            });
+        }
+
+        /// Private function firing the `AddressChanged` for the address setting `key`
+        fn address_changed(
+            &self,
+            key: ConfigKey,
+            old_address: Option<AccountId>,
+            new_address: Option<AccountId>,
+        ) {
+            self.emit(AddressChanged {
+                key: key as u8,
+                old_address,
+                new_address,
+            });
+        }
+
+        /// Private function firing the `AccountSettingChanged` for `account`'s setting `key`
+        fn account_setting_changed(
+            &self,
+            key: ConfigKey,
+            account: AccountId,
+            old_value: u128,
+            new_value: u128,
+        ) {
+            self.emit(AccountSettingChanged {
+                key: key as u8,
+                account,
+                old_value,
+                new_value,
+            });
+        }
+
+        /// Private guard for the mint messages, renounced minting is reported by `mint_to`
+        fn ensure_minter(&self) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner && !self.minters.contains(caller) {
+                return Err(Error::NotMinter);
+            }
+            if !self.minting_active() {
+                return Err(Error::MintingNotActive);
+            }
+            Ok(())
+        }
+
+        /// Private check whether the mint grace period is over
+        fn minting_active(&self) -> bool {
+            self.mint_activation_block
+                .is_none_or(|block| self.env().block_number() >= block)
+        }
+
+        /// Private guard for the owner-only messages
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        /// Private function behind `transfer_from`, moves `amount` from `from` to `to` on behalf of `spender`
+        /// and spends that much of its allowance. An infinite allowance of `Balance::MAX` is never spent down
+        fn transfer_from_as(
+            &mut self,
+            spender: AccountId,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            let allowance = self.allowance(*from, spender);
+            let remaining = allowance
+                .checked_sub(amount)
+                .ok_or(Error::InsufficientAllowance)?;
+            if let Some(recipient) = self.allowance_recipients.get((*from, spender)) {
+                if recipient != *to {
+                    return Err(Error::RecipientNotAllowed);
+                }
+            }
+
+            self.transfer_from_to(from, to, amount)?;
+            if allowance != Balance::MAX {
+                self.store_allowance(*from, spender, remaining);
+                // a standing order tops itself up again, as far as the owner's balance still covers
+                if let Some(refill_to) = self.allowance_refills.get((*from, spender)) {
+                    let refilled = refill_to.min(self.balance_of(*from));
+                    if refilled > remaining {
+                        self.write_allowance(*from, spender, refilled);
+                    }
+                }
+            }
+
+            // a limited-use allowance is gone after its last spend, whatever is left of it
+            if let Some(uses) = self.allowance_uses.get((*from, spender)) {
+                if uses > 1 {
+                    self.allowance_uses.insert((*from, spender), &(uses - 1));
+                } else {
+                    self.allowance_uses.remove((*from, spender));
+                    self.allowance_refills.remove((*from, spender));
+                    self.allowance_recipients.remove((*from, spender));
+                    self.write_allowance(*from, spender, 0);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Private guard for the bridge-only messages
+        fn ensure_bridge(&self) -> Result<()> {
+            if self.bridge != Some(self.env().caller()) {
+                return Err(Error::NotBridge);
+            }
+            Ok(())
+        }
+
+        /// Private function to handle the logic of tranfers, the sender pays `amount` once and the transfer
+        /// fee (split among the fee recipients) and the burn fee are taken out of it before `to` gets the rest
+        fn transfer_from_to(
+            &mut self,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            self.ensure_transfer_allowed(from, to, amount)?;
+            self.spend_daily_volume(from, amount)?;
+            self.ensure_launch_rate(from)?;
+            self.record_outflow(amount);
+            if *to == zero_account() {
+                return self.hold_accidental(from, amount);
+            }
+
+            let (fee, burned) = self.fees_for(from, to, amount);
+            let split_fee = if self.reflect_fees {
+                self.reflect(from, fee)?;
+                0
+            } else if self.fees_to_reserve {
+                self.accrue_reserve(from, fee);
+                0
+            } else {
+                fee
+            };
+            let mut remaining_fee = split_fee;
+            let splits = self.fee_splits.clone();
+            for (i, (recipient, split_bps)) in splits.iter().enumerate() {
+                // the last recipient picks up the rounding remainder so the whole fee is handed out
+                let cut = if i + 1 == splits.len() {
+                    remaining_fee
+                } else {
+                    portion(split_fee, *split_bps, self.transfer_fee_bps)
+                };
+                if cut > 0 {
+                    if *recipient == self.env().account_id() {
+                        self.collect_fee(from, cut);
+                    } else {
+                        self.move_balance(from, recipient, cut)?;
+                    }
+                    remaining_fee -= cut;
+                }
+            }
+            // a tier fee charged without any splits to pay it to
+            if remaining_fee > 0 {
+                self.collect_fee(from, remaining_fee);
+            }
+
+            if burned > 0 {
+                self.burn_from(from, burned)?;
+            }
+
+            let net = amount - fee - burned;
+            self.move_balance(from, to, net)?;
+            self.record_transfer(from, to, net);
+            self.record_transfer(to, from, net);
+            self.notify_balance_change(from);
+            self.notify_balance_change(to);
+            self.ensure_acknowledged(from, to, net)
+        }
+
+        /// Private read-only checks a transfer has to pass before anything moves, shared with
+        /// `simulate_transfer_from`
+        fn ensure_transfer_allowed(
+            &self,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            if !self.transferable {
+                return Err(Error::NonTransferable);
+            }
+            if self.transfers_paused {
+                return Err(Error::Paused);
+            }
+            if self.env().block_number() < self.vote_lock_of(*from) {
+                return Err(Error::StillLocked);
+            }
+            if amount < self.min_transfer {
+                return Err(Error::TransferTooSmall);
+            }
+            if self.max_transfer > 0 && amount > self.max_transfer {
+                return Err(Error::TransferTooLarge);
+            }
+            self.ensure_policy_allows(from, to, amount)?;
+            let balance = self.balance_of(*from);
+            if balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            // sending the whole balance is always allowed, leaving dust isn't
+            let left = balance - amount;
+            if left > 0 && left < self.min_balance {
+                return Err(Error::BelowMinimum);
+            }
+            self.daily_volume_after(from, amount)?;
+            self.ensure_launch_slot(from)
+        }
+
+        /// Private function sharing `fee` out of `from`'s balance among every holder through the
+        /// reflection index. A fee too small to move the index at all stays with the sender rather than
+        /// being lost
+        fn reflect(&mut self, from: &AccountId, fee: Balance) -> Result<()> {
+            let held = self.total_held - fee;
+            if held == 0 {
+                return Ok(());
+            }
+            let growth = mul_div(fee, self.reflection_index, held)?;
+            if growth == 0 {
+                return Ok(());
+            }
+
+            let balance = self.balance_of(*from);
+            self.set_balance(from, balance - fee);
+            self.reflection_index += growth;
+            // the holders' growth is credited passively, the held total takes it up in one go
+            self.total_held += fee;
+
+            self.emit(Reflected {
+                from: *from,
+                value: fee,
+            });
+            Ok(())
+        }
+
+        /// Private circuit breaker, adds `amount` to the outflow of the current window and pauses
+        /// transfers once that passes `breaker_threshold`. The transfer tripping it still goes through,
+        /// failing it would revert the pause along with it
+        fn record_outflow(&mut self, amount: Balance) {
+            if self.breaker_threshold == Balance::MAX {
+                return;
+            }
+            let window = self.env().block_number() / self.breaker_window;
+            let outflow = match self.outflow {
+                (last_window, outflow) if last_window == window => outflow.saturating_add(amount),
+                _ => amount,
+            };
+            self.outflow = (window, outflow);
+            if outflow > self.breaker_threshold {
+                self.transfers_paused = true;
+                self.emit(CircuitBreakerTripped { window, outflow });
+            }
+        }
+
+        /// Private guard for the anti-sniping cooldown, lets `from` send only once per block from
+        /// `launch_block` until the cooldown runs out
+        fn ensure_launch_rate(&mut self, from: &AccountId) -> Result<()> {
+            let block = self.env().block_number();
+            if block < self.launch_block || block - self.launch_block >= self.launch_cooldown {
+                return Ok(());
+            }
+            self.ensure_launch_slot(from)?;
+            self.last_launch_transfer.insert(from, &block);
+            Ok(())
+        }
+
+        /// Private read-only half of `ensure_launch_rate`, fails if `from` already sent in this block
+        /// of the cooldown
+        fn ensure_launch_slot(&self, from: &AccountId) -> Result<()> {
+            let block = self.env().block_number();
+            if block < self.launch_block || block - self.launch_block >= self.launch_cooldown {
+                return Ok(());
+            }
+            if self.last_launch_transfer.get(from) == Some(block) {
+                return Err(Error::TransferCooldown);
+            }
+            Ok(())
+        }
+
+        /// Private guard for the daily limit, adds `amount` to what `from` sent in the current window
+        /// and fails if that goes past `daily_limit`. The volume starts over in every new window
+        fn spend_daily_volume(&mut self, from: &AccountId, amount: Balance) -> Result<()> {
+            if self.daily_limit == Balance::MAX {
+                return Ok(());
+            }
+            let window = self.env().block_number() / self.limit_window;
+            let sent = self.daily_volume_after(from, amount)?;
+            self.sent_volume.insert(from, &(window, sent));
+            Ok(())
+        }
+
+        /// Private read-only half of `spend_daily_volume`, what `from` would have sent in the window
+        /// after `amount` more
+        fn daily_volume_after(&self, from: &AccountId, amount: Balance) -> Result<Balance> {
+            if self.daily_limit == Balance::MAX {
+                return Ok(0);
+            }
+            self.sent_in_window(*from)
+                .checked_add(amount)
+                .filter(|sent| *sent <= self.daily_limit)
+                .ok_or(Error::DailyLimitExceeded)
+        }
+
+        /// Private fee calculation for a transfer of `amount`, returning the transfer fee and the burn fee
+        /// rounded as `round_fee_up` says. Both are waived when either side is fee-exempt
+        fn fees_for(
+            &self,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> (Balance, Balance) {
+            if self.fee_exempt.contains(from) || self.fee_exempt.contains(to) {
+                return (0, 0);
+            }
+            let round = if self.round_fee_up {
+                portion_ceil
+            } else {
+                portion
+            };
+            let fee = round(amount, self.transfer_fee_bps_for(from, to), BPS_DENOMINATOR);
+            // rounding both up could take more than a dust amount has
+            let burned = round(amount, self.burn_fee_bps, BPS_DENOMINATOR).min(amount - fee);
+            (fee, burned)
+        }
+
+        /// Private lookup of the transfer fee rate `from` pays sending to `to`: the rate of `to`'s class
+        /// if it has one, else of the highest fee tier `from`'s balance reaches or the plain
+        /// `transfer_fee_bps` below them all
+        fn transfer_fee_bps_for(&self, from: &AccountId, to: &AccountId) -> u16 {
+            if let Some(fee_bps) = self.class_fees.get(self.classification_of(*to)) {
+                return fee_bps;
+            }
+            let balance = self.balance_of(*from);
+            self.fee_tiers
+                .iter()
+                .rev()
+                .find(|(min_balance, _)| balance >= *min_balance)
+                .map_or(self.transfer_fee_bps, |(_, fee_bps)| *fee_bps)
+        }
+
+        /// Private function appending a transfer to `account`'s history, dropping the oldest entry
+        /// once it holds `RECENT_TRANSFERS_CAP`
+        fn record_transfer(
+            &mut self,
+            account: &AccountId,
+            counterparty: &AccountId,
+            amount: Balance,
+        ) {
+            let mut history = self.recent_transfers.get(account).unwrap_or_default();
+            if history.len() == RECENT_TRANSFERS_CAP {
+                history.remove(0);
+            }
+            history.push((*counterparty, amount, self.env().block_number()));
+            self.recent_transfers.insert(account, &history);
+        }
+
+        /// Private function moving `amount` straight from one balance to another and firing the `Transfer`
+        fn move_balance(
+            &mut self,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            let sender_balance = self.balance_of(*from);
+
+            if sender_balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            // a sender handing over its whole balance frees the slot the recipient takes
+            if from == to || sender_balance != amount {
+                self.ensure_holder_room(to, amount)?;
+            }
+
+            self.set_balance(from, sender_balance - amount);
+            let to_balance = self.balance_of(*to);
+            self.set_balance(to, to_balance + amount);
+
+            self.emit(Transfer {
+                from: Some(*from),
+                to: Some(*to),
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Private check against the policy contract, if one is set. Fails closed: a policy that
+        /// isn't a contract or whose `check_transfer` reverts rejects the transfer like a `false` does
+        fn ensure_policy_allows(
+            &self,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            let Some(policy) = self.policy else {
+                return Ok(());
+            };
+            if !self.env().is_contract(&policy) {
+                return Err(Error::PolicyRejected);
+            }
+
+            // a plain call without reentry, the policy only gets to look
+            let allowed = build_call::<Environment>()
+                .call(policy)
+                .gas_limit(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(CHECK_TRANSFER_SELECTOR))
+                        .push_arg(from)
+                        .push_arg(to)
+                        .push_arg(amount),
+                )
+                .returns::<bool>()
+                .try_invoke();
+            if !matches!(allowed, Ok(Ok(true))) {
+                return Err(Error::PolicyRejected);
+            }
+            Ok(())
+        }
+
+        /// Private hook for the acknowledgement mode, calls `to`'s `on_tokens_received` with reentry
+        /// allowed and fails unless `to` called `acknowledge_receipt` during it
+        fn ensure_acknowledged(
+            &mut self,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            if !self.requires_ack || !self.ack_receivers.contains(to) || !self.env().is_contract(to)
+            {
+                return Ok(());
+            }
+
+            self.pending_acks.insert(to, &());
+            let called = self.invoke_reentrant(
+                *to,
+                ExecutionInput::new(Selector::new(ON_TOKENS_RECEIVED_SELECTOR))
+                    .push_arg(from)
+                    .push_arg(amount),
+            );
+
+            // still pending means the hook reverted or never called back
+            let acknowledged = called && !self.pending_acks.contains(to);
+            self.pending_acks.remove(to);
+            if !acknowledged {
+                return Err(Error::ReceiverNotAcknowledged);
+            }
+            Ok(())
+        }
+
+        /// Private function behind the approving messages, sets an unlimited-use allowance subject to
+        /// the allowance cap
+        fn approve_from(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            self.ensure_allowance_cap(owner, spender, amount)?;
+            self.allowance_uses.remove((owner, spender));
+            self.allowance_refills.remove((owner, spender));
+            self.allowance_recipients.remove((owner, spender));
+            self.write_allowance(owner, spender, amount);
+            Ok(())
+        }
+
+        /// Private read of `owner`'s exposure checkpoint at `index`
+        fn exposure_checkpoint(&self, owner: AccountId, index: u32) -> ExposureCheckpoint {
+            self.exposure_checkpoints
+                .get((owner, index))
+                .unwrap_or_default()
+        }
+
+        /// Private function writing an allowance and firing the `Approval`
+        fn write_allowance(&mut self, owner: AccountId, spender: AccountId, amount: Balance) {
+            self.store_allowance(owner, spender, amount);
+
+            self.emit(Approval {
+                owner,
+                spender,
+                amount,
+            });
+        }
+
+        /// Private allowance write without an event, keeps the owner's granted total in step. The total
+        /// saturates since unlimited allowances of `Balance::MAX` can't be summed
+        fn store_allowance(&mut self, owner: AccountId, spender: AccountId, amount: Balance) {
+            let previous = self.allowance(owner, spender);
+            let granted = self.total_approved_by(owner);
+            let block = self.env().block_number();
+            let count = self
+                .exposure_checkpoint_count
+                .get(owner)
+                .unwrap_or_default();
+            if count == 0 || self.exposure_checkpoint(owner, count - 1).0 < block {
+                self.exposure_checkpoints
+                    .insert((owner, count), &(block, granted));
+                self.exposure_checkpoint_count.insert(owner, &(count + 1));
+            }
+            let total = granted.saturating_sub(previous).saturating_add(amount);
+            self.total_allowance_granted.insert(owner, &total);
+            let count = self.approval_count(owner);
+            match (previous, amount) {
+                (0, 1..) => {
+                    self.approval_counts.insert(owner, &(count + 1));
+                }
+                (1.., 0) => {
+                    self.approval_counts.insert(owner, &(count - 1));
+                }
+                _ => {}
+            }
+            self.allowances.insert((owner, spender), &amount);
+        }
+
+        /// Private permit verification, checks the deadline and that `signature` recovers to `owner`
+        /// over the digest at the owner's current nonce, then consumes that nonce
+        fn use_permit(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            signature: [u8; 65],
+            single_use: bool,
+        ) -> Result<()> {
+            if self.env().block_timestamp() > deadline {
+                return Err(Error::PermitExpired);
+            }
+
+            let digest = self.permit_digest(owner, spender, value, deadline, single_use);
+            let mut public_key = [0u8; 33];
+            ink::env::ecdsa_recover(&signature, &digest, &mut public_key)
+                .map_err(|_| Error::InvalidSignature)?;
+            // substrate derives ECDSA accounts as the blake2 hash of the compressed public key
+            let signer = AccountId::from(self.env().hash_bytes::<Blake2x256>(&public_key));
+            if signer != owner {
+                return Err(Error::InvalidSignature);
+            }
+
+            self.nonces.insert(owner, &(self.nonces(owner) + 1));
+            Ok(())
+        }
+
+        /// Private guard for every message granting an allowance, against the global cap, the one
+        /// on `spender` and the number of spenders `owner` may have approved at once
+        fn ensure_allowance_cap(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            // paused approvals still let owners shrink what they granted
+            if self.approvals_paused && amount > self.allowance(owner, spender) {
+                return Err(Error::ApprovalsPaused);
+            }
+            if amount > self.max_allowance || amount > self.spender_cap(spender) {
+                return Err(Error::AllowanceTooHigh);
+            }
+            if amount > 0
+                && self.allowance(owner, spender) == 0
+                && self.approval_count(owner) >= self.max_approvals_per_owner
+            {
+                return Err(Error::TooManyApprovals);
+            }
+            Ok(())
+        }
+
+        /// Private cross-contract call with reentry allowed, returning whether the callee succeeded.
+        /// The root storage is flushed before and reloaded after, otherwise the callee calling back in
+        /// would read stale values and our own write at the end would clobber whatever it changed
+        fn invoke_reentrant<Args: scale::Encode>(
+            &mut self,
+            callee: AccountId,
+            input: ExecutionInput<Args>,
+        ) -> bool {
+            let key = <Self as ink::storage::traits::StorageKey>::KEY;
+            let entered = core::mem::replace(&mut self.entered, true);
+            ink::env::set_contract_storage(&key, self);
+
+            let called = build_call::<Environment>()
+                .call(callee)
+                .gas_limit(0)
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(input)
+                .returns::<()>()
+                .try_invoke();
+
+            if let Ok(Some(reloaded)) = ink::env::get_contract_storage::<_, Self>(&key) {
+                *self = reloaded;
+            }
+            self.entered = entered;
+            matches!(called, Ok(Ok(())))
+        }
+
+        /// Private function dropping queued transfer `id` and crediting its amount back to `from`
+        fn release_queued(&mut self, id: u32, from: &AccountId, amount: Balance) {
+            self.transfer_requests.remove(id);
+            self.total_queued -= amount;
+            let balance = self.balance_of(*from);
+            self.set_balance(from, balance + amount);
+        }
+
+        /// Private function removing `subscriber` from `account`'s subscribers, if it is one
+        fn drop_subscriber(&mut self, account: AccountId, subscriber: AccountId) {
+            let mut subscribers = self.balance_subscribers(account);
+            subscribers.retain(|watcher| *watcher != subscriber);
+            if subscribers.is_empty() {
+                self.balance_subscribers.remove(account);
+            } else {
+                self.balance_subscribers.insert(account, &subscribers);
+            }
+        }
+
+        /// Private best-effort notification of `account`'s subscribers, a subscriber that isn't a
+        /// contract, reverts or runs out of its `ON_BALANCE_CHANGE_GAS` is skipped and can't block
+        /// the transfer. Plain calls without reentry
+        fn notify_balance_change(&self, account: &AccountId) {
+            let Some(subscribers) = self.balance_subscribers.get(account) else {
+                return;
+            };
+            let new_balance = self.balance_of(*account);
+            for subscriber in subscribers {
+                if !self.env().is_contract(&subscriber) {
+                    continue;
+                }
+                let _ = build_call::<Environment>()
+                    .call(subscriber)
+                    .gas_limit(ON_BALANCE_CHANGE_GAS)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ON_BALANCE_CHANGE_SELECTOR))
+                            .push_arg(account)
+                            .push_arg(new_balance),
+                    )
+                    .returns::<()>()
+                    .try_invoke();
+            }
+        }
+
+        /// Private guard for the messages taking a token id other than 0
+        fn ensure_token(&self, token_id: u32) -> Result<()> {
+            if !self.token_symbols.contains(token_id) {
+                return Err(Error::UnknownToken);
+            }
+            Ok(())
+        }
+
+        /// Private guard for the callback-bearing messages, failing while a reentrant callback is in
+        /// flight unless the guard was switched off
+        fn ensure_not_reentered(&self) -> Result<()> {
+            if self.reentrancy_guard_enabled && self.entered {
+                return Err(Error::Reentrant);
+            }
+            Ok(())
+        }
+    }
+
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(test)]
+    mod tests {
         /// Imports all the definitions from the outer scope so we can use them here.
         use super::*;     
+        use std::collections::BTreeMap;
+
+        type Event = <MockDai as ::ink::reflect::ContractEventBase>::Type;
+
+        /// Deterministic test account `n`, account 1 being the default caller and owner
+        fn account(n: u8) -> AccountId {
+            AccountId::from([n; 32])
+        }
+
+        /// Mints `amount` to `to` for test setup, as the owner whoever the current caller is
+        fn fund(mock_dai: &mut MockDai, to: AccountId, amount: Balance) {
+            let caller = ink::env::caller::<Environment>();
+            ink::env::test::set_caller::<Environment>(mock_dai.owner);
+            mock_dai.mint(to, amount).unwrap();
+            ink::env::test::set_caller::<Environment>(caller);
+        }
+
+        /// A deliberately broken fixture for the conservation check: a stake that no balance paid for
+        fn broken_accounting() -> MockDai {
+            let mut mock_dai = MockDai::new(1_000);
+            mock_dai.stake(100).unwrap();
+            mock_dai.total_staked += 1;
+            mock_dai
+        }
+
+        /// Every `ConfigChanged` recorded so far as `(key, old_value, new_value)`
+        fn config_changes() -> Vec<(u8, u128, u128)> {
+            ink::env::test::recorded_events()
+                .filter_map(|event| {
+                    match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                        Event::ConfigChanged(ConfigChanged {
+                            key,
+                            old_value,
+                            new_value,
+                        }) => Some((key, old_value, new_value)),
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+
+        /// Rebuilds every balance the way an indexer would, by replaying the recorded `Transfer` and
+        /// `Burn` events. Balances moved by the escrow features (staking, locks, dividends) or by fee
+        /// reflection fire their own events and aren't replayed, so only compare it against runs that
+        /// don't use them
+        fn reconstruct_balances_from_events() -> BTreeMap<AccountId, Balance> {
+            let mut balances = BTreeMap::new();
+            for event in ink::env::test::recorded_events() {
+                match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                    Event::Transfer(Transfer { from, to, value }) => {
+                        if let Some(from) = from {
+                            *balances.entry(from).or_default() -= value;
+                        }
+                        if let Some(to) = to {
+                            *balances.entry(to).or_default() += value;
+                        }
+                    }
+                    Event::Burn(Burn { from, value }) => {
+                        *balances.entry(from).or_default() -= value
+                    }
+                    _ => {}
+                }
+            }
+            balances
+        }
+
+        /// Signs a permit digest with a test ECDSA key
+        fn sign_permit(secret: [u8; 32], digest: [u8; 32]) -> [u8; 65] {
+            let secp = secp256k1::Secp256k1::new();
+            let key = secp256k1::SecretKey::from_slice(&secret).unwrap();
+            let message = secp256k1::Message::from_slice(&digest).unwrap();
+            let (recovery_id, compact) = secp
+                .sign_ecdsa_recoverable(&message, &key)
+                .serialize_compact();
+
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            signature
+        }
+
+        /// The account a test ECDSA key signs permits for
+        fn permit_signer(secret: [u8; 32]) -> AccountId {
+            let secp = secp256k1::Secp256k1::new();
+            let key = secp256k1::SecretKey::from_slice(&secret).unwrap();
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &key).serialize();
+            let mut account = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut account);
+            AccountId::from(account)
+        }
+
+        /// We test if the default constructor does its job.
+        #[ink::test]
+        fn constructor_works() {
+            let mock_dai = MockDai::new(1_000_000);
+            assert_eq!(mock_dai.total_supply(), 1_000_000);
+        }
+
+        /// We write a test to simulate transfers and then return balances of Bob and Alice
+        #[ink::test]
+        fn balance_of_returns_correct_values() {
+            // deploy an instance of MockDai token
+            let mut mock_dai = MockDai::new(1_000_000);
+            // make some mock accounts as we would with `makeAddr` in foundry equivalent
+            
+            // @note keep in mind that when we make mock addresses like we do below, the address derived from Account::from([1; 32]) is just the same as we do in a foundry test where address(this) is the calling contract during testing. so, to create actual accounts where the msg.sender isn't the deployer contract/address, we just skip making an address from 1.
+            // this contract/deployer/msg.sender = account(1);
+            let bob = account(2);
+            let alice = account(3);
+
+            // check that before the DAI transfer, bob doesn't have any token balance
+            assert_eq!(mock_dai.balance_of(bob), 0);
+
+            // transfer some DAI to bob
+            mock_dai.transfer(bob, 500).unwrap(); // we unwrap to return the actual value of the transfer transaction a.k.a result
+
+            // check that bob recieved 500 DAI
+            assert_eq!(mock_dai.balance_of(bob), 500);
+
+            // check that during the transfer to bob, ALice didn't mistakenly get DAI tokens
+            assert_eq!(mock_dai.balance_of(alice), 0);
+        }
+
+        #[ink::test]
+        fn do_an_approval_check() {
+            // @note if we were only making a read from the contract we can lose the `mut` key like below
+            let mock_dai = MockDai::new(1_000_000);
+
+            let bob = account(2);
+            let alice = account(3);
+
+            // make sure that there is no current allowances from bob to alice
+            assert_eq!(mock_dai.allowance(bob, alice), 0);
+        }
+
+        /// The owner sweeps only the listed balances that sit below the dust threshold
+        #[ink::test]
+        fn sweep_dust_moves_only_sub_threshold_balances() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let charlie = account(4);
+
+            // bob and charlie end up holding dust, alice holds a real balance
+            mock_dai.transfer(bob, 5).unwrap();
+            mock_dai.transfer(alice, 500).unwrap();
+            mock_dai.transfer(charlie, 9).unwrap();
+            let events_before = ink::env::test::recorded_events().count();
+
+            mock_dai.sweep_dust(vec![bob, alice, charlie], 10).unwrap();
+
+            assert_eq!(mock_dai.balance_of(bob), 0);
+            assert_eq!(mock_dai.balance_of(charlie), 0);
+            assert_eq!(mock_dai.balance_of(alice), 500);
+            assert_eq!(mock_dai.balance_of(owner), 1_000_000 - 500);
+            // one `Transfer` per swept account
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 2);
+
+            // any other caller is rejected
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(
+                mock_dai.sweep_dust(vec![alice], 1_000),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// Known interface ids are detected while unknown ones are not
+        #[ink::test]
+        fn supports_interface_detects_known_ids() {
+            let mock_dai = MockDai::new(1_000_000);
+
+            assert!(mock_dai.supports_interface(INTERFACE_ID_ERC165));
+            assert!(mock_dai.supports_interface(INTERFACE_ID_ERC20));
+            assert!(mock_dai.supports_interface(INTERFACE_ID_PERMIT));
+            assert!(mock_dai.supports_interface(INTERFACE_ID_METADATA));
+            assert_eq!(mock_dai.name(), TOKEN_NAME);
+            assert_eq!(mock_dai.symbol(), TOKEN_SYMBOL);
+            // a lone message selector isn't an interface id
+            assert!(!mock_dai.supports_interface(ink::selector_bytes!("transfer")));
+            // ERC165 reserves 0xffffffff as never supported
+            assert!(!mock_dai.supports_interface([0xff; 4]));
+        }
+
+        /// A 1% fee split 60/40 between two recipients is taken once out of the sent amount
+        #[ink::test]
+        fn transfer_fee_is_split_between_recipients() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let charlie = account(4);
+
+            mock_dai
+                .set_transfer_fee(100, vec![(bob, 60), (charlie, 40)])
+                .unwrap();
+            mock_dai.transfer(alice, 10_000).unwrap();
+
+            assert_eq!(mock_dai.balance_of(owner), 1_000_000 - 10_000);
+            assert_eq!(mock_dai.balance_of(alice), 9_900);
+            assert_eq!(mock_dai.balance_of(bob), 60);
+            assert_eq!(mock_dai.balance_of(charlie), 40);
+            assert_eq!(mock_dai.total_supply(), 1_000_000);
+        }
+
+        /// Fee splits that don't add up to the fee are rejected
+        #[ink::test]
+        fn set_transfer_fee_rejects_mismatched_splits() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let charlie = account(4);
+
+            assert_eq!(
+                mock_dai.set_transfer_fee(100, vec![(bob, 60), (charlie, 30)]),
+                Err(Error::InvalidFeeConfig)
+            );
+            assert_eq!(
+                mock_dai.set_transfer_fee(100, Vec::new()),
+                Err(Error::InvalidFeeConfig)
+            );
+            assert_eq!(mock_dai.transfer_fee_bps(), 0);
+        }
+
+        /// Each holder of the distribution snapshot claims a proportional share of the dividend, once
+        #[ink::test]
+        fn dividends_are_claimed_pro_rata_once() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let charlie = account(4);
+
+            // bob holds a quarter of the supply when the dividend is distributed
+            mock_dai.transfer(bob, 250_000).unwrap();
+            let epoch = mock_dai.distribute(1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(owner), 750_000 - 1_000);
+
+            // moving tokens after the snapshot doesn't change anyone's share
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(charlie, 100_000).unwrap();
+            assert_eq!(mock_dai.balance_at(bob, epoch), Ok(250_000));
+            assert_eq!(mock_dai.claim_dividend(epoch), Ok(250));
+            assert_eq!(mock_dai.balance_of(bob), 150_250);
+
+            ink::env::test::set_caller::<Environment>(owner);
+            assert_eq!(mock_dai.claim_dividend(epoch), Ok(750));
+            assert_eq!(mock_dai.balance_of(owner), 749_750);
+
+            // a second claim for the same epoch is rejected
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(
+                mock_dai.claim_dividend(epoch),
+                Err(Error::DividendAlreadyClaimed)
+            );
+            assert_eq!(
+                mock_dai.claim_dividend(epoch + 1),
+                Err(Error::DividendNotFound)
+            );
+        }
+
+        /// A 2% burn fee shrinks the supply while the recipient gets the net amount
+        #[ink::test]
+        fn burn_fee_reduces_total_supply() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+
+            mock_dai.set_burn_fee(200).unwrap();
+            let events_before = ink::env::test::recorded_events().count();
+            mock_dai.transfer(bob, 10_000).unwrap();
+
+            // a `Transfer` for the net amount, bob's `FirstReceipt` and a `Burn` for the fee
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 3);
+            assert_eq!(mock_dai.balance_of(owner), 990_000);
+            assert_eq!(mock_dai.balance_of(bob), 9_800);
+            assert_eq!(mock_dai.total_supply(), 1_000_000 - 200);
+
+            // fees above 10% are rejected
+            assert_eq!(mock_dai.set_burn_fee(1_001), Err(Error::FeeTooHigh));
+            assert_eq!(mock_dai.burn_fee_bps(), 200);
+        }
+
+        /// The receipt hash is deterministic and commits to every field
+        #[ink::test]
+        fn transfer_receipt_hash_commits_to_every_field() {
+            let mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let alice = account(3);
+
+            let hash = mock_dai.transfer_receipt_hash(bob, alice, 500, 7);
+            assert_eq!(hash, mock_dai.transfer_receipt_hash(bob, alice, 500, 7));
+
+            assert_ne!(hash, mock_dai.transfer_receipt_hash(alice, bob, 500, 7));
+            assert_ne!(hash, mock_dai.transfer_receipt_hash(bob, bob, 500, 7));
+            assert_ne!(hash, mock_dai.transfer_receipt_hash(bob, alice, 501, 7));
+            assert_ne!(hash, mock_dai.transfer_receipt_hash(bob, alice, 500, 8));
+        }
+
+        /// A failed message leaves its reason behind until the next mutating call succeeds
+        #[ink::test]
+        fn last_error_reason_is_recorded_and_cleared() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            assert_eq!(mock_dai.last_error_reason(), None);
+
+            assert_eq!(
+                mock_dai.transfer(bob, 1_000_001),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(
+                mock_dai.last_error_reason(),
+                Some(String::from(Error::InsufficientBalance.reason()))
+            );
+
+            mock_dai.transfer(bob, 1).unwrap();
+            assert_eq!(mock_dai.last_error_reason(), None);
+        }
+
+        /// EOAs stay exempt from the acknowledgement mode even when registered. A registered contract
+        /// acknowledging from its hook needs a deployed receiver, which the off-chain engine can't call
+        #[ink::test]
+        fn ack_mode_exempts_eoa_receivers() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+
+            mock_dai.set_requires_ack(true).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.register_ack_receiver(true).unwrap();
+            assert!(mock_dai.is_ack_receiver(bob));
+
+            // bob isn't a contract, so the transfer doesn't wait on an acknowledgement
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.transfer(bob, 500).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 500);
+
+            // nothing is pending for bob to acknowledge
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.acknowledge_receipt(), Err(Error::NoPendingReceipt));
+        }
+
+        /// A single-use permit is fully revoked by its first spend even if value is left over
+        #[ink::test]
+        fn single_use_permit_is_revoked_after_one_spend() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let alice = account(3);
+            let secret = [7; 32];
+            let signer = permit_signer(secret);
+            mock_dai.transfer(signer, 1_000).unwrap();
+
+            let digest = mock_dai.permit_digest(signer, bob, 500, 100, true);
+            let signature = sign_permit(secret, digest);
+
+            // the single-use signature doesn't pass as a plain permit
+            assert_eq!(
+                mock_dai.permit(signer, bob, 500, 100, signature),
+                Err(Error::InvalidSignature)
+            );
+            mock_dai
+                .permit_single_use(signer, bob, 500, 100, signature)
+                .unwrap();
+            assert_eq!(mock_dai.allowance(signer, bob), 500);
+            assert_eq!(mock_dai.nonces(signer), 1);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer_from(signer, alice, 200).unwrap();
+            assert_eq!(mock_dai.balance_of(alice), 200);
+            assert_eq!(mock_dai.allowance(signer, bob), 0);
+            assert_eq!(
+                mock_dai.transfer_from(signer, alice, 1),
+                Err(Error::InsufficientAllowance)
+            );
+        }
+
+        /// A permit can't be replayed, used past its deadline or signed by someone else
+        #[ink::test]
+        fn permit_rejects_replays_expiry_and_foreign_signers() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let secret = [7; 32];
+            let signer = permit_signer(secret);
+
+            let digest = mock_dai.permit_digest(signer, bob, 500, 100, false);
+            let signature = sign_permit(secret, digest);
+            mock_dai.permit(signer, bob, 500, 100, signature).unwrap();
+            assert_eq!(mock_dai.allowance(signer, bob), 500);
+            assert_eq!(
+                mock_dai.permit(signer, bob, 500, 100, signature),
+                Err(Error::InvalidSignature)
+            );
+
+            let digest = mock_dai.permit_digest(signer, bob, 900, 100, false);
+            let foreign = sign_permit([8; 32], digest);
+            assert_eq!(
+                mock_dai.permit(signer, bob, 900, 100, foreign),
+                Err(Error::InvalidSignature)
+            );
+
+            let signature = sign_permit(secret, digest);
+            ink::env::test::set_block_timestamp::<Environment>(101);
+            assert_eq!(
+                mock_dai.permit(signer, bob, 900, 100, signature),
+                Err(Error::PermitExpired)
+            );
+            assert_eq!(mock_dai.allowance(signer, bob), 500);
+        }
+
+        /// Approvals above the allowance cap are rejected while ones at the cap go through
+        #[ink::test]
+        fn approvals_respect_the_allowance_cap() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            assert_eq!(mock_dai.max_allowance(), Balance::MAX);
+
+            mock_dai.set_max_allowance(1_000).unwrap();
+            assert_eq!(mock_dai.approve(bob, 1_001), Err(Error::AllowanceTooHigh));
+            mock_dai.approve(bob, 1_000).unwrap();
+            assert_eq!(mock_dai.allowance(account(1), bob), 1_000);
+
+            // increasing past the cap fails too, lowering is always fine
+            assert_eq!(
+                mock_dai.increase_allowance(bob, 1),
+                Err(Error::AllowanceTooHigh)
+            );
+            mock_dai.decrease_allowance(bob, 400).unwrap();
+            mock_dai.increase_allowance(bob, 400).unwrap();
+            assert_eq!(mock_dai.allowance(account(1), bob), 1_000);
+        }
+
+        /// Staked, locked and treasury tokens don't count as circulating
+        #[ink::test]
+        fn circulating_supply_excludes_staked_locked_and_treasury() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let treasury = account(9);
+            assert_eq!(mock_dai.circulating_supply(), 1_000_000);
+
+            mock_dai.set_treasury(Some(treasury)).unwrap();
+            mock_dai.transfer(treasury, 100_000).unwrap();
+            mock_dai.transfer(bob, 50_000).unwrap();
+            mock_dai.stake(200_000).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.lock(30_000, 5).unwrap();
+            assert_eq!(
+                mock_dai.circulating_supply(),
+                1_000_000 - 100_000 - 200_000 - 30_000
+            );
+            assert_eq!(mock_dai.total_supply(), 1_000_000);
+
+            // locked tokens only come back once the unlock block is reached
+            assert_eq!(mock_dai.release_lock(), Err(Error::StillLocked));
+            for _ in 0..5 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(mock_dai.release_lock(), Ok(30_000));
+            assert_eq!(mock_dai.balance_of(bob), 50_000);
+
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.unstake(200_000).unwrap();
+            assert_eq!(mock_dai.circulating_supply(), 1_000_000 - 100_000);
+        }
+
+        /// A soulbound token rejects transfers while minting and burning keep working
+        #[ink::test]
+        fn soulbound_mode_blocks_transfers_but_not_mint_and_burn() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.approve(bob, 100).unwrap();
+
+            mock_dai.set_transferable(false).unwrap();
+            assert_eq!(mock_dai.transfer(bob, 100), Err(Error::NonTransferable));
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(
+                mock_dai.transfer_from(owner, bob, 100),
+                Err(Error::NonTransferable)
+            );
+
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.mint(bob, 500).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 500);
+            assert_eq!(mock_dai.total_supply(), 1_000_500);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.burn(200).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 300);
+            assert_eq!(mock_dai.total_supply(), 1_000_300);
+
+            // switching it back on restores transfers
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.set_transferable(true).unwrap();
+            mock_dai.transfer(bob, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 400);
+        }
+
+        /// The approval part of `approve_and_call` is validated before the spender is ever called. The
+        /// call itself, with either selector, needs a deployed spender and isn't covered off-chain
+        #[ink::test]
+        fn approve_and_call_checks_the_allowance_cap_first() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let spender = account(5);
+
+            mock_dai.set_max_allowance(1_000).unwrap();
+            assert_eq!(
+                mock_dai.approve_and_call_with_selector(
+                    spender,
+                    1_001,
+                    ink::selector_bytes!("on_approval"),
+                    Vec::new()
+                ),
+                Err(Error::AllowanceTooHigh)
+            );
+            assert_eq!(
+                mock_dai.approve_and_call(spender, 1_001, Vec::new()),
+                Err(Error::AllowanceTooHigh)
+            );
+            assert_eq!(mock_dai.allowance(owner, spender), 0);
+        }
+
+        /// Batches of exactly `MAX_BATCH` entries go through while one more fails fast
+        #[ink::test]
+        fn batches_are_capped_at_max_batch() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let full = vec![(bob, 1); MAX_BATCH];
+            let over = vec![(bob, 1); MAX_BATCH + 1];
+
+            mock_dai.batch_transfer(full.clone()).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), MAX_BATCH as Balance);
+            mock_dai.batch_approve(full.clone()).unwrap();
+            assert_eq!(mock_dai.allowance(owner, bob), 1);
+            mock_dai.mint_batch(full).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 2 * MAX_BATCH as Balance);
+
+            assert_eq!(
+                mock_dai.batch_transfer(over.clone()),
+                Err(Error::BatchTooLarge)
+            );
+            assert_eq!(
+                mock_dai.batch_approve(over.clone()),
+                Err(Error::BatchTooLarge)
+            );
+            assert_eq!(mock_dai.mint_batch(over), Err(Error::BatchTooLarge));
+            assert_eq!(
+                mock_dai.sweep_dust(vec![bob; MAX_BATCH + 1], Balance::MAX),
+                Err(Error::BatchTooLarge)
+            );
+            assert_eq!(mock_dai.balance_of(bob), 2 * MAX_BATCH as Balance);
+        }
+
+        /// Replaying the emitted events rebuilds exactly the balances in storage
+        #[ink::test]
+        fn events_replay_to_the_stored_balances() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let charlie = account(4);
+
+            mock_dai
+                .set_transfer_fee(100, vec![(charlie, 100)])
+                .unwrap();
+            mock_dai.set_burn_fee(50).unwrap();
+            mock_dai.mint(bob, 5_000).unwrap();
+            mock_dai.transfer(alice, 20_000).unwrap();
+            ink::env::test::set_caller::<Environment>(alice);
+            mock_dai.transfer(bob, 7_000).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.burn(1_000).unwrap();
+
+            let reconstructed = reconstruct_balances_from_events();
+            assert_eq!(reconstructed.len(), 4);
+            for account in [owner, bob, alice, charlie] {
+                assert_eq!(reconstructed[&account], mock_dai.balance_of(account));
+            }
+            assert_eq!(
+                reconstructed.values().sum::<Balance>(),
+                mock_dai.total_supply()
+            );
+        }
+
+        /// Going from 18 to 27 decimals multiplies every balance and the supply by 10^9
+        #[ink::test]
+        fn redenominate_scales_balances_and_decimals() {
+            let mut mock_dai = MockDai::new(1_000_000 * 10u128.pow(18));
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.transfer(bob, 250 * 10u128.pow(18)).unwrap();
+            assert_eq!(mock_dai.decimals(), 18);
+
+            mock_dai.redenominate(27, 10u128.pow(9), true).unwrap();
+            assert_eq!(mock_dai.decimals(), 27);
+            assert_eq!(mock_dai.total_supply(), 1_000_000 * 10u128.pow(27));
+            assert_eq!(mock_dai.balance_of(owner), 999_750 * 10u128.pow(27));
+            assert_eq!(mock_dai.balance_of(bob), 250 * 10u128.pow(27));
+
+            // transfers keep working in the new unit
+            mock_dai.transfer(bob, 1).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 250 * 10u128.pow(27) + 1);
+            assert_eq!(mock_dai.balance_of(owner), 999_750 * 10u128.pow(27) - 1);
+            assert!(mock_dai.check_conservation());
+
+            // a supply that would overflow is rejected
+            assert_eq!(
+                mock_dai.redenominate(40, Balance::MAX, true),
+                Err(Error::Overflow)
+            );
+            assert_eq!(mock_dai.decimals(), 27);
+        }
+
+        /// Dividing is rejected, balances that don't divide would otherwise round down to nothing
+        #[ink::test]
+        fn redenominate_rejects_dividing() {
+            let mut mock_dai = MockDai::new(10);
+            let bob = account(2);
+            mock_dai.transfer(bob, 5).unwrap();
+
+            assert_eq!(mock_dai.redenominate(0, 10, false), Err(Error::Overflow));
+            assert_eq!(mock_dai.redenominate(0, 1, false), Err(Error::Overflow));
+            assert_eq!(mock_dai.redenominate(18, 0, true), Err(Error::Overflow));
+            assert_eq!(mock_dai.total_supply(), 10);
+            assert_eq!(mock_dai.balance_of(bob), 5);
+            assert_eq!(mock_dai.holder_count(), 2);
+            assert!(mock_dai.check_conservation());
+        }
+
+        /// The caller's own balance comes back without passing an address
+        #[ink::test]
+        fn my_balance_returns_the_callers_balance() {
+            let mock_dai = MockDai::new(1_000_000);
+            assert_eq!(mock_dai.my_balance(), 1_000_000);
+
+            ink::env::test::set_caller::<Environment>(account(2));
+            assert_eq!(mock_dai.my_balance(), 0);
+        }
+
+        /// A merchant pulls once per interval, spending its allowance each time
+        #[ink::test]
+        fn subscription_pulls_respect_the_interval() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let merchant = account(2);
+            mock_dai.approve(merchant, 300).unwrap();
+
+            ink::env::test::set_caller::<Environment>(merchant);
+            mock_dai.pull_subscription(owner, 100, 10).unwrap();
+            assert_eq!(mock_dai.balance_of(merchant), 100);
+            assert_eq!(mock_dai.allowance(owner, merchant), 200);
+
+            // pulling again right away is too early
+            assert_eq!(
+                mock_dai.pull_subscription(owner, 100, 10),
+                Err(Error::TransferCooldown)
+            );
+
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            mock_dai.pull_subscription(owner, 100, 10).unwrap();
+            assert_eq!(mock_dai.balance_of(merchant), 200);
+            assert_eq!(mock_dai.last_pull_block(owner, merchant), Some(10));
+        }
+
+        /// The version reports the code constant and flips its flag after `initialize`
+        #[ink::test]
+        fn version_reports_initialization() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            assert_eq!(mock_dai.version(), (CONTRACT_VERSION, false));
+
+            mock_dai.initialize().unwrap();
+            assert_eq!(mock_dai.version(), (CONTRACT_VERSION, true));
+            assert_eq!(mock_dai.initialize(), Err(Error::AlreadyInitialized));
+        }
+
+        /// Transfers touching a fee-exempt account don't pay the fee
+        #[ink::test]
+        fn fee_exempt_accounts_skip_the_fee() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let collector = account(4);
+            mock_dai
+                .set_transfer_fee(100, vec![(collector, 100)])
+                .unwrap();
+
+            // a normal transfer pays 1%
+            mock_dai.transfer(bob, 10_000).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 9_900);
+            assert_eq!(mock_dai.balance_of(collector), 100);
+
+            // an exempt sender pays nothing
+            mock_dai.set_fee_exempt(owner, true).unwrap();
+            assert!(mock_dai.is_fee_exempt(owner));
+            mock_dai.transfer(alice, 10_000).unwrap();
+            assert_eq!(mock_dai.balance_of(alice), 10_000);
+
+            // nor does a transfer between two exempt accounts
+            mock_dai.set_fee_exempt(alice, true).unwrap();
+            ink::env::test::set_caller::<Environment>(alice);
+            mock_dai.transfer(owner, 5_000).unwrap();
+            assert_eq!(mock_dai.balance_of(alice), 5_000);
+            assert_eq!(mock_dai.balance_of(collector), 100);
+        }
+
+        /// Only the trusted bridge mints and burns the bridged supply
+        #[ink::test]
+        fn bridge_mints_and_burns() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let bridge = account(8);
+            mock_dai.set_bridge(Some(bridge)).unwrap();
+
+            // nobody else can use the bridge messages, the owner included
+            assert_eq!(mock_dai.bridge_mint(bob, 500, 1), Err(Error::NotBridge));
+
+            ink::env::test::set_caller::<Environment>(bridge);
+            mock_dai.bridge_mint(bob, 500, 1).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 500);
+            mock_dai.bridge_burn(bob, 200).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 300);
+            assert_eq!(mock_dai.total_supply(), 1_000_300);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.bridge_burn(bob, 300), Err(Error::NotBridge));
+        }
+
+        /// A bridge message is minted once, a replay is rejected and a new nonce goes through
+        #[ink::test]
+        fn bridge_mint_rejects_replayed_nonces() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let bridge = account(8);
+            mock_dai.set_bridge(Some(bridge)).unwrap();
+            ink::env::test::set_caller::<Environment>(bridge);
+
+            mock_dai.bridge_mint(bob, 500, 42).unwrap();
+            assert!(mock_dai.is_nonce_processed(42));
+            assert_eq!(
+                mock_dai.bridge_mint(bob, 500, 42),
+                Err(Error::NonceAlreadyProcessed)
+            );
+            assert_eq!(mock_dai.balance_of(bob), 500);
+
+            mock_dai.bridge_mint(bob, 500, 43).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+        }
+
+        /// Spending exactly the allowance leaves zero, spending past it errors
+        #[ink::test]
+        fn transfer_from_spends_the_allowance_down_to_zero() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            mock_dai.approve(bob, 300).unwrap();
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(
+                mock_dai.transfer_from(owner, alice, 301),
+                Err(Error::InsufficientAllowance)
+            );
+            mock_dai.transfer_from(owner, alice, 300).unwrap();
+            assert_eq!(mock_dai.allowance(owner, bob), 0);
+            assert_eq!(mock_dai.balance_of(alice), 300);
+            assert_eq!(
+                mock_dai.transfer_from(owner, alice, 1),
+                Err(Error::InsufficientAllowance)
+            );
+        }
+
+        /// The transfer history keeps only the latest entries, in order
+        #[ink::test]
+        fn recent_transfers_keep_the_last_entries() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+
+            // two more transfers than the history holds, one per block
+            let transfers = RECENT_TRANSFERS_CAP as Balance + 2;
+            for amount in 1..=transfers {
+                mock_dai.transfer(bob, amount).unwrap();
+                ink::env::test::advance_block::<Environment>();
+            }
+
+            // the two oldest ones were dropped on both sides
+            let expected: TransferHistory = (3..=transfers)
+                .map(|amount| (owner, amount, amount as BlockNumber - 1))
+                .collect();
+            assert_eq!(mock_dai.recent_transfers_of(bob), expected);
+            let history = mock_dai.recent_transfers_of(owner);
+            assert_eq!(history.len(), RECENT_TRANSFERS_CAP);
+            assert_eq!(history[0], (bob, 3, 2));
+        }
+
+        /// Once the holder cap is reached only existing holders can receive tokens
+        #[ink::test]
+        fn holder_cap_blocks_new_holders_only() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let alice = account(3);
+            let charlie = account(4);
+            mock_dai.set_max_holders(3).unwrap();
+
+            mock_dai.transfer(bob, 100).unwrap();
+            mock_dai.transfer(alice, 100).unwrap();
+            assert_eq!(mock_dai.holder_count(), 3);
+
+            assert_eq!(
+                mock_dai.transfer(charlie, 100),
+                Err(Error::HolderCapReached)
+            );
+            assert_eq!(mock_dai.mint(charlie, 100), Err(Error::HolderCapReached));
+            mock_dai.transfer(bob, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 200);
+
+            // draining a holder frees its slot
+            ink::env::test::set_caller::<Environment>(alice);
+            mock_dai.transfer(charlie, 100).unwrap();
+            assert_eq!(mock_dai.holder_count(), 3);
+            assert_eq!(mock_dai.balance_of(charlie), 100);
+        }
+
+        /// An allowance approved for two uses is revoked by the second spend
+        #[ink::test]
+        fn n_use_allowance_expires_after_last_use() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            mock_dai.approve_n_uses(bob, 1_000, 2).unwrap();
+            assert_eq!(mock_dai.allowance_uses(owner, bob), Some(2));
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer_from(owner, alice, 100).unwrap();
+            assert_eq!(mock_dai.allowance(owner, bob), 900);
+            assert_eq!(mock_dai.allowance_uses(owner, bob), Some(1));
+            mock_dai.transfer_from(owner, alice, 100).unwrap();
+            assert_eq!(mock_dai.allowance(owner, bob), 0);
+            assert_eq!(mock_dai.allowance_uses(owner, bob), None);
+
+            assert_eq!(
+                mock_dai.transfer_from(owner, alice, 100),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(mock_dai.balance_of(alice), 200);
+        }
+
+        /// The granted total follows approvals, decreases and spends across spenders
+        #[ink::test]
+        fn total_approved_by_sums_outstanding_allowances() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            mock_dai.approve(bob, 1_000).unwrap();
+            mock_dai.approve(alice, 500).unwrap();
+            assert_eq!(mock_dai.total_approved_by(owner), 1_500);
+
+            mock_dai.decrease_allowance(bob, 400).unwrap();
+            assert_eq!(mock_dai.total_approved_by(owner), 1_100);
+
+            ink::env::test::set_caller::<Environment>(alice);
+            mock_dai.transfer_from(owner, alice, 200).unwrap();
+            assert_eq!(mock_dai.total_approved_by(owner), 900);
+        }
+
+        /// With reflection on a passive holder's balance grows with every fee paid
+        #[ink::test]
+        fn reflected_fees_grow_passive_balances() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let collector = account(4);
+            mock_dai.transfer(bob, 500_000).unwrap();
+            mock_dai
+                .set_transfer_fee(100, vec![(collector, 100)])
+                .unwrap();
+            mock_dai.set_reflect_fees(true).unwrap();
+
+            // the 1% fee on 100_000 is shared among the 999_000 still held, alice held nothing yet
+            mock_dai.transfer(alice, 100_000).unwrap();
+            let bob_balance = mock_dai.balance_of(bob);
+            assert_eq!(bob_balance, 500_500);
+            assert_eq!(mock_dai.balance_of(alice), 99_000);
+            assert_eq!(mock_dai.balance_of(collector), 0);
+            assert!(mock_dai.reflection_index() > REFLECTION_PRECISION);
+
+            // no tokens are created along the way
+            let held = mock_dai.balance_of(owner) + bob_balance + mock_dai.balance_of(alice);
+            assert!(held <= mock_dai.total_supply());
+            assert!(mock_dai.total_supply() - held < 3);
+        }
+
+        /// A transfer may empty the sender or leave it above the minimum, but not in between
+        #[ink::test]
+        fn min_balance_rejects_dust_left_behind() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let alice = account(3);
+            mock_dai.set_min_balance(1_000).unwrap();
+            mock_dai.transfer(bob, 5_000).unwrap();
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.transfer(alice, 4_500), Err(Error::BelowMinimum));
+            assert_eq!(mock_dai.balance_of(bob), 5_000);
+
+            mock_dai.transfer(alice, 4_000).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+            mock_dai.transfer(alice, 1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 0);
+            assert_eq!(mock_dai.balance_of(alice), 5_000);
+        }
+
+        /// Historical exposure reads the granted total as each block began
+        #[ink::test]
+        fn approval_exposure_is_checkpointed_per_block() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            mock_dai.approve(bob, 1_000).unwrap();
+            ink::env::test::advance_block::<Environment>();
+            mock_dai.approve(alice, 500).unwrap();
+            mock_dai.increase_allowance(alice, 100).unwrap();
+            ink::env::test::advance_block::<Environment>();
+            mock_dai.decrease_allowance(bob, 400).unwrap();
+            ink::env::test::advance_block::<Environment>();
+
+            assert_eq!(mock_dai.approval_exposure_at(owner, 0), 0);
+            assert_eq!(mock_dai.approval_exposure_at(owner, 1), 1_000);
+            assert_eq!(mock_dai.approval_exposure_at(owner, 2), 1_600);
+            assert_eq!(mock_dai.approval_exposure_at(owner, 3), 1_200);
+        }
+
+        /// Once minting is renounced every mint path fails for good
+        #[ink::test]
+        fn renounced_minting_blocks_every_mint_path() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.mint(bob, 100).unwrap();
+            mock_dai.set_bridge(Some(owner)).unwrap();
+            mock_dai.bridge_mint(bob, 100, 1).unwrap();
+
+            mock_dai.renounce_minting().unwrap();
+            assert!(mock_dai.minting_renounced());
+            assert_eq!(mock_dai.mint(bob, 100), Err(Error::MintingRenounced));
+            assert_eq!(
+                mock_dai.mint_batch(vec![(bob, 100)]),
+                Err(Error::MintingRenounced)
+            );
+            assert_eq!(
+                mock_dai.bridge_mint(bob, 100, 2),
+                Err(Error::MintingRenounced)
+            );
+            assert_eq!(mock_dai.balance_of(bob), 200);
+            assert_eq!(mock_dai.total_supply(), 1_000_200);
+        }
+
+        /// Without a policy transfers pass, a policy that can't be asked rejects them. Allowing and denying
+        /// policies need deployed contracts, which the off-chain engine can't call
+        #[ink::test]
+        fn unreachable_policy_rejects_transfers() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let policy = account(9);
+            mock_dai.transfer(bob, 100).unwrap();
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.set_policy(Some(policy)), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Environment>(account(1));
+            mock_dai.set_policy(Some(policy)).unwrap();
+            assert_eq!(mock_dai.transfer(bob, 100), Err(Error::PolicyRejected));
+
+            mock_dai.set_policy(None).unwrap();
+            mock_dai.transfer(bob, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 200);
+        }
+
+        /// Only an account never credited would get a new balance entry
+        #[ink::test]
+        fn would_create_entry_for_fresh_accounts_only() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            assert!(!mock_dai.would_create_entry(owner));
+            assert!(mock_dai.would_create_entry(bob));
+
+            mock_dai.transfer(bob, 100).unwrap();
+            assert!(!mock_dai.would_create_entry(bob));
+        }
+
+        /// Holders move between the distribution buckets as their balances cross thresholds
+        #[ink::test]
+        fn holder_distribution_follows_balances() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let bucket = |mock_dai: &MockDai, threshold: Balance| {
+                mock_dai
+                    .holder_distribution()
+                    .into_iter()
+                    .find(|(lower, _)| *lower == threshold)
+                    .unwrap()
+                    .1
+            };
+            assert_eq!(bucket(&mock_dai, 1_000_000), 1);
+
+            mock_dai.transfer(bob, 999).unwrap();
+            assert_eq!(bucket(&mock_dai, 1), 1);
+            assert_eq!(bucket(&mock_dai, 1_000), 1);
+            assert_eq!(bucket(&mock_dai, 1_000_000), 0);
+
+            mock_dai.transfer(bob, 1).unwrap();
+            assert_eq!(bucket(&mock_dai, 1), 0);
+            assert_eq!(bucket(&mock_dai, 1_000), 2);
+
+            mock_dai.burn(999_000).unwrap();
+            assert_eq!(bucket(&mock_dai, 1_000), 1);
+            let holders: u32 = mock_dai.holder_distribution().iter().map(|(_, n)| n).sum();
+            assert_eq!(holders, mock_dai.holder_count());
+        }
+
+        /// Split `v`, `r`, `s` components make the same permit as the 65-byte signature
+        #[ink::test]
+        fn permit_vrs_accepts_split_signatures() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let secret = [7; 32];
+            let signer = permit_signer(secret);
+
+            let digest = mock_dai.permit_digest(signer, bob, 500, 100, false);
+            let signature = sign_permit(secret, digest);
+            let (r, s) = (
+                signature[..32].try_into().unwrap(),
+                signature[32..64].try_into().unwrap(),
+            );
+            mock_dai
+                .permit_vrs(signer, bob, 500, 100, signature[64] + 27, r, s)
+                .unwrap();
+            assert_eq!(mock_dai.allowance(signer, bob), 500);
+            assert_eq!(mock_dai.nonces(signer), 1);
+        }
+
+        /// Owner mints pay the treasury its cut on top, growing the supply by both
+        #[ink::test]
+        fn mint_pays_the_treasury_cut() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let treasury = account(9);
+            assert_eq!(
+                mock_dai.set_treasury_mint_bps(1_001),
+                Err(Error::FeeTooHigh)
+            );
+            mock_dai.set_treasury(Some(treasury)).unwrap();
+            mock_dai.set_treasury_mint_bps(500).unwrap();
+
+            mock_dai.mint(bob, 10_000).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 10_000);
+            assert_eq!(mock_dai.balance_of(treasury), 500);
+            assert_eq!(mock_dai.total_supply(), 1_010_500);
+        }
+
+        /// Sends count against the daily limit until the window rolls over
+        #[ink::test]
+        fn daily_limit_resets_with_the_window() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.set_daily_limit(1_000, 10).unwrap();
+
+            mock_dai.transfer(bob, 600).unwrap();
+            mock_dai.transfer(bob, 400).unwrap();
+            assert_eq!(mock_dai.sent_in_window(owner), 1_000);
+            assert_eq!(mock_dai.transfer(bob, 1), Err(Error::DailyLimitExceeded));
+
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(mock_dai.sent_in_window(owner), 0);
+            mock_dai.transfer(bob, 1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 2_000);
+        }
+
+        /// The transfer and the approval land together, or not at all
+        #[ink::test]
+        fn transfer_and_approve_is_atomic() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let router = account(5);
+            mock_dai
+                .transfer_and_approve(bob, 1_000, router, 500)
+                .unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+            assert_eq!(mock_dai.allowance(owner, router), 500);
+            // the constructor's mint, then the Transfer, bob's FirstReceipt and the Approval
+            assert_eq!(ink::env::test::recorded_events().count(), 4);
+
+            assert_eq!(
+                mock_dai.transfer_and_approve(bob, 2_000_000, router, 900),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+            assert_eq!(mock_dai.allowance(owner, router), 500);
+        }
+
+        /// Native currency swaps in at the peg and back out of the reserve
+        #[ink::test]
+        fn native_swaps_mint_and_burn_at_the_peg() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let contract = account(10);
+            assert_eq!(mock_dai.set_native_peg(0), Err(Error::InvalidPeg));
+            mock_dai.set_native_peg(2).unwrap();
+
+            ink::env::test::set_callee::<Environment>(contract);
+            ink::env::test::set_account_balance::<Environment>(contract, 1_000);
+            ink::env::test::set_account_balance::<Environment>(bob, 0);
+            ink::env::test::set_caller::<Environment>(bob);
+            ink::env::test::set_value_transferred::<Environment>(500);
+            assert_eq!(mock_dai.swap_native_for_dai(), Ok(1_000));
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+
+            ink::env::test::set_value_transferred::<Environment>(0);
+            assert_eq!(mock_dai.swap_dai_for_native(401), Ok(200));
+            assert_eq!(mock_dai.balance_of(bob), 600);
+            assert_eq!(mock_dai.total_supply(), 1_000_600);
+            assert_eq!(
+                ink::env::test::get_account_balance::<Environment>(bob),
+                Ok(200)
+            );
+            ink::env::test::set_account_balance::<Environment>(contract, 100);
+            assert_eq!(
+                mock_dai.swap_dai_for_native(600),
+                Err(Error::InsufficientReserve)
+            );
+        }
+
+        /// Changing the transfer fee leaves an audit event with the old and new values
+        #[ink::test]
+        fn transfer_fee_change_fires_config_changed() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let collector = account(4);
+            mock_dai
+                .set_transfer_fee(100, vec![(collector, 100)])
+                .unwrap();
+            mock_dai
+                .set_transfer_fee(250, vec![(collector, 250)])
+                .unwrap();
+
+            let key = ConfigKey::TransferFee as u8;
+            assert_eq!(config_changes(), vec![(key, 0, 100), (key, 100, 250)]);
+        }
+
+        /// Class fees and the stabilizer parameters leave the same audit trail, unset values as 0
+        #[ink::test]
+        fn class_fee_and_stabilizer_changes_fire_config_changed() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            mock_dai.set_class_fee(CLASS_EXCHANGE, Some(300)).unwrap();
+            mock_dai.set_class_fee(CLASS_EXCHANGE, None).unwrap();
+            mock_dai.set_stabilizer(Some((1_000, 5_000, 100))).unwrap();
+            mock_dai.set_stabilizer(None).unwrap();
+
+            let class = ConfigKey::ClassFeeExchange as u8;
+            let reserve = ConfigKey::StabilizerReserve as u8;
+            let ratio = ConfigKey::StabilizerRatio as u8;
+            let step = ConfigKey::StabilizerStep as u8;
+            assert_eq!(
+                config_changes(),
+                vec![
+                    (class, 0, 300),
+                    (class, 300, 0),
+                    (reserve, 0, 1_000),
+                    (ratio, 0, 5_000),
+                    (step, 0, 100),
+                    (reserve, 1_000, 0),
+                    (ratio, 5_000, 0),
+                    (step, 100, 0),
+                ]
+            );
+        }
+
+        /// Tokens sent to the zero address are held aside until the owner recovers them
+        #[ink::test]
+        fn zero_address_sends_are_recoverable() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let zero = account(0);
+            mock_dai.transfer(zero, 1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(zero), 0);
+            assert_eq!(mock_dai.accidental_burns(), 1_000);
+            assert_eq!(mock_dai.total_supply(), 1_000_000);
+            assert_eq!(mock_dai.circulating_supply(), 999_000);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.recover_accidental(bob), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Environment>(account(1));
+            assert_eq!(mock_dai.recover_accidental(bob), Ok(1_000));
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+            assert_eq!(mock_dai.accidental_burns(), 0);
+        }
+
+        /// The owner passed to `new_with_owner` holds the owner-only powers, not the deployer
+        #[ink::test]
+        fn new_with_owner_separates_owner_from_deployer() {
+            let deployer = account(1);
+            let governance = account(6);
+            let mut mock_dai = MockDai::new_with_owner(1_000_000, governance);
+            assert_eq!(mock_dai.owner(), governance);
+            assert_eq!(mock_dai.balance_of(deployer), 1_000_000);
+            assert_eq!(mock_dai.set_burn_fee(100), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Environment>(governance);
+            mock_dai.set_burn_fee(100).unwrap();
+            assert_eq!(mock_dai.burn_fee_bps(), 100);
+        }
+
+        /// The selector table lists every message with its dispatched selector, in the order the
+        /// generated metadata has them
+        #[ink::test]
+        fn selector_table_matches_computed_selectors() {
+            extern "Rust" {
+                // emitted by `#[ink::contract]` under the std feature, what cargo-contract calls too
+                #[link_name = "__ink_generate_metadata"]
+                fn generate_metadata() -> ink::metadata::InkProject;
+            }
+            let project = unsafe { generate_metadata() };
+            let generated: Vec<(String, [u8; 4])> = project
+                .spec()
+                .messages()
+                .iter()
+                .map(|message| {
+                    let selector = message.selector().to_bytes().try_into().unwrap();
+                    (message.label().clone(), selector)
+                })
+                .collect();
+            assert_eq!(selector_table(), generated);
+
+            let table = selector_table();
+            let transfer = table.iter().find(|(label, _)| label == "transfer").unwrap();
+            assert_eq!(transfer.1, ink::selector_bytes!("transfer"));
+        }
+
+        /// `psp22_transfer` answers on the PSP22-standardized selector and moves tokens like `transfer`
+        #[ink::test]
+        fn transfer_selector_matches_psp22() {
+            let psp22 = SELECTOR_TABLE
+                .iter()
+                .find(|(label, _)| *label == "psp22_transfer")
+                .unwrap();
+            assert_eq!(psp22.1, [0xdb, 0x20, 0xf9, 0xf5]);
+            assert_eq!(psp22.1, ink::selector_bytes!("PSP22::transfer"));
+
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            mock_dai
+                .psp22_transfer(bob, 100, Vec::from(*b"memo"))
+                .unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 100);
+            assert_eq!(
+                mock_dai.psp22_transfer(bob, 1_000, Vec::new()),
+                Err(Error::InsufficientBalance)
+            );
+        }
+
+        /// The allowance only changes while it still holds the expected value
+        #[ink::test]
+        fn compare_and_approve_detects_concurrent_spends() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.compare_and_approve(bob, 0, 1_000).unwrap();
+            assert_eq!(mock_dai.allowance(owner, bob), 1_000);
+
+            // bob spends in between, so lowering from the stale 1_000 fails
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer_from(owner, bob, 400).unwrap();
+            ink::env::test::set_caller::<Environment>(owner);
+            assert_eq!(
+                mock_dai.compare_and_approve(bob, 1_000, 100),
+                Err(Error::AllowanceChanged)
+            );
+            assert_eq!(mock_dai.allowance(owner, bob), 600);
+            mock_dai.compare_and_approve(bob, 600, 100).unwrap();
+            assert_eq!(mock_dai.allowance(owner, bob), 100);
+        }
+
+        /// Minting is open to the owner and granted minters until it's renounced
+        #[ink::test]
+        fn can_mint_follows_minters_and_renouncing() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let minter = account(2);
+            let random = account(3);
+            mock_dai.set_minter(minter, true).unwrap();
+            assert!(mock_dai.can_mint(owner));
+            assert!(mock_dai.can_mint(minter));
+            assert!(!mock_dai.can_mint(random));
+
+            ink::env::test::set_caller::<Environment>(minter);
+            mock_dai.mint(minter, 100).unwrap();
+            ink::env::test::set_caller::<Environment>(random);
+            assert_eq!(mock_dai.mint(random, 100), Err(Error::NotMinter));
+
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.renounce_minting().unwrap();
+            assert!(!mock_dai.can_mint(owner));
+            assert!(!mock_dai.can_mint(minter));
+        }
+
+        /// During the launch cooldown an account sends once per block, afterwards freely
+        #[ink::test]
+        fn launch_cooldown_limits_one_transfer_per_block() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            ink::env::test::advance_block::<Environment>();
+            mock_dai.set_launch(1, 3).unwrap();
+
+            mock_dai.transfer(bob, 100).unwrap();
+            assert_eq!(mock_dai.transfer(bob, 100), Err(Error::TransferCooldown));
+            ink::env::test::advance_block::<Environment>();
+            mock_dai.transfer(bob, 100).unwrap();
+
+            for _ in 0..2 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            mock_dai.transfer(bob, 100).unwrap();
+            mock_dai.transfer(bob, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 400);
+        }
+
+        /// The allowance matrix is flattened one owner row at a time
+        #[ink::test]
+        fn allowance_matrix_is_row_major() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let charlie = account(4);
+            mock_dai.approve(alice, 1).unwrap();
+            mock_dai.approve(charlie, 2).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.approve(alice, 3).unwrap();
+            mock_dai.approve(charlie, 4).unwrap();
+
+            assert_eq!(
+                mock_dai.allowance_matrix(vec![owner, bob], vec![alice, charlie]),
+                Ok(vec![1, 2, 3, 4])
+            );
+            assert_eq!(
+                mock_dai.allowance_matrix(vec![owner; 11], vec![alice; 10]),
+                Err(Error::BatchTooLarge)
+            );
+        }
+
+        /// A window's outflow passing the threshold pauses transfers until the owner unpauses
+        #[ink::test]
+        fn circuit_breaker_pauses_on_large_outflows() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            mock_dai.set_circuit_breaker(10_000, 100).unwrap();
+
+            mock_dai.transfer(bob, 6_000).unwrap();
+            mock_dai.transfer(bob, 4_000).unwrap();
+            assert!(!mock_dai.paused());
+
+            // the transfer tripping the breaker goes through, the next one doesn't
+            mock_dai.transfer(bob, 1).unwrap();
+            assert!(mock_dai.paused());
+            assert_eq!(mock_dai.transfer(bob, 1), Err(Error::Paused));
+            assert_eq!(mock_dai.balance_of(bob), 10_001);
+
+            mock_dai.unpause().unwrap();
+            mock_dai.transfer(bob, 1).unwrap();
+            assert!(!mock_dai.paused());
+        }
+
+        /// Uneven fees round either way while sender, recipient and collector still add up
+        #[ink::test]
+        fn fee_rounding_conserves_the_amount() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let alice = account(3);
+            let collector = account(4);
+            mock_dai
+                .set_transfer_fee(150, vec![(collector, 150)])
+                .unwrap();
+
+            // 1.5% of 1_001 is 15.015
+            mock_dai.transfer(bob, 1_001).unwrap();
+            assert_eq!(mock_dai.balance_of(collector), 15);
+            assert_eq!(mock_dai.balance_of(bob), 986);
+
+            mock_dai.set_round_fee_up(true).unwrap();
+            mock_dai.transfer(alice, 1_001).unwrap();
+            assert_eq!(mock_dai.balance_of(collector), 15 + 16);
+            assert_eq!(mock_dai.balance_of(alice), 985);
+            assert_eq!(mock_dai.balance_of(account(1)), 1_000_000 - 2 * 1_001);
+        }
+
+        /// Sweeping moves the allowance or the balance, whichever is smaller
+        #[ink::test]
+        fn transfer_from_all_moves_the_smaller_of_allowance_and_balance() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            mock_dai.approve(alice, 300).unwrap();
+            mock_dai.transfer(bob, 500).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.approve(alice, Balance::MAX).unwrap();
+
+            ink::env::test::set_caller::<Environment>(alice);
+            assert_eq!(mock_dai.transfer_from_all(owner, alice), Ok(300));
+            assert_eq!(mock_dai.allowance(owner, alice), 0);
+
+            // bob's infinite approval stays infinite
+            assert_eq!(mock_dai.transfer_from_all(bob, alice), Ok(500));
+            assert_eq!(mock_dai.balance_of(bob), 0);
+            assert_eq!(mock_dai.allowance(bob, alice), Balance::MAX);
+            assert_eq!(mock_dai.balance_of(alice), 800);
+        }
+
+        /// Accounts can be tagged with short labels and read back
+        #[ink::test]
+        fn account_tags_are_capped() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            assert_eq!(mock_dai.tag_of(bob), None);
+
+            mock_dai.set_tag(bob, b"whale".to_vec()).unwrap();
+            assert_eq!(mock_dai.tag_of(bob), Some(b"whale".to_vec()));
+            assert_eq!(
+                mock_dai.set_tag(bob, vec![b'x'; MAX_TAG_LEN + 1]),
+                Err(Error::TagTooLong)
+            );
+            assert_eq!(mock_dai.tag_of(bob), Some(b"whale".to_vec()));
+
+            mock_dai.set_tag(bob, Vec::new()).unwrap();
+            assert_eq!(mock_dai.tag_of(bob), None);
+        }
+
+        /// An account's first receipt is announced once, draining and refilling doesn't repeat it
+        #[ink::test]
+        fn first_receipt_fires_once_per_account() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let first_receipts = || {
+                ink::env::test::recorded_events()
+                    .filter_map(|event| {
+                        match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                            Event::FirstReceipt(FirstReceipt { account, .. }) => Some(account),
+                            _ => None,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            mock_dai.transfer(bob, 100).unwrap();
+            mock_dai.transfer(bob, 100).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(owner, 200).unwrap();
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.transfer(bob, 100).unwrap();
+            assert_eq!(first_receipts(), vec![bob]);
+        }
+
+        /// Large holders pay the discounted tier rate, with every party still adding up
+        #[ink::test]
+        fn fee_tiers_discount_large_holders() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let collector = account(4);
+            mock_dai.transfer(bob, 10_000).unwrap();
+            mock_dai
+                .set_transfer_fee(200, vec![(collector, 200)])
+                .unwrap();
+            assert_eq!(
+                mock_dai.set_fee_tiers(vec![(100_000, 100), (50_000, 50)]),
+                Err(Error::InvalidFeeConfig)
+            );
+            mock_dai
+                .set_fee_tiers(vec![(50_000, 100), (100_000, 50)])
+                .unwrap();
+
+            // bob is below every tier and pays the full 2%
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(alice, 1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(collector), 20);
+            assert_eq!(mock_dai.balance_of(alice), 980);
+
+            // the owner is in the top tier and pays 0.5%
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.transfer(alice, 1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(collector), 20 + 5);
+            assert_eq!(mock_dai.balance_of(alice), 980 + 995);
+            let held = [owner, bob, alice, collector]
+                .iter()
+                .map(|account| mock_dai.balance_of(*account))
+                .sum::<Balance>();
+            assert_eq!(held, mock_dai.total_supply());
+        }
+
+        /// Quotes add up to the amount and match what the transfer then does
+        #[ink::test]
+        fn quote_transfer_previews_fees() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            let alice = account(3);
+            let collector = account(4);
+            mock_dai.transfer(bob, 10_000).unwrap();
+            mock_dai
+                .set_transfer_fee(200, vec![(collector, 200)])
+                .unwrap();
+            mock_dai.set_burn_fee(100).unwrap();
+
+            assert_eq!(mock_dai.quote_transfer(bob, alice, 1_000), (970, 30));
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(alice, 1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(alice), 970);
+
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.set_fee_exempt(alice, true).unwrap();
+            assert_eq!(mock_dai.quote_transfer(bob, alice, 1_000), (1_000, 0));
+
+            mock_dai.set_fee_tiers(vec![(100_000, 50)]).unwrap();
+            let (net, fee) = mock_dai.quote_transfer(owner, bob, 1_000);
+            assert_eq!((net, fee), (985, 15));
+            assert_eq!(net + fee, 1_000);
+        }
+
+        /// Migrating hands the whole allowance to the new spender and zeroes the old one
+        #[ink::test]
+        fn migrate_allowance_moves_it_to_the_new_spender() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let old_router = account(5);
+            let new_router = account(6);
+            mock_dai.approve(old_router, 700).unwrap();
+            let events_before = ink::env::test::recorded_events().count();
+
+            mock_dai.migrate_allowance(old_router, new_router).unwrap();
+            assert_eq!(mock_dai.allowance(owner, old_router), 0);
+            assert_eq!(mock_dai.allowance(owner, new_router), 700);
+            assert_eq!(mock_dai.total_approved_by(owner), 700);
+            // an `Approval` for each spender
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 2);
+        }
+
+        /// Fees split to the contract itself accrue until the owner withdraws them
+        #[ink::test]
+        fn collected_fees_accrue_and_withdraw() {
+            // the contract's storage lives under its own account, so move there before deploying
+            let contract = account(10);
+            ink::env::test::set_callee::<Environment>(contract);
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let treasurer = account(6);
+            mock_dai
+                .set_transfer_fee(100, vec![(contract, 100)])
+                .unwrap();
+
+            mock_dai.transfer(bob, 10_000).unwrap();
+            mock_dai.transfer(bob, 20_000).unwrap();
+            assert_eq!(mock_dai.collected_fees(), 300);
+            assert_eq!(mock_dai.balance_of(contract), 0);
+            assert_eq!(mock_dai.balance_of(bob), 29_700);
+
+            assert_eq!(mock_dai.withdraw_fees(treasurer), Ok(300));
+            assert_eq!(mock_dai.balance_of(treasurer), 300);
+            assert_eq!(mock_dai.collected_fees(), 0);
+        }
+
+        /// Tokens moved through sub-accounts always add back up to what the account held
+        #[ink::test]
+        fn sub_accounts_reconcile_with_the_liquid_balance() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            mock_dai.deposit_to_sub(1, 300).unwrap();
+            mock_dai.move_between_subs(1, 2, 100).unwrap();
+            assert_eq!(
+                mock_dai.move_between_subs(1, 2, 201),
+                Err(Error::InsufficientBalance)
+            );
+            mock_dai.withdraw_from_sub(2, 50).unwrap();
+
+            assert_eq!(mock_dai.balance_of(owner), 999_750);
+            assert_eq!(mock_dai.sub_balance_of(owner, 1), 200);
+            assert_eq!(mock_dai.sub_balance_of(owner, 2), 50);
+            let total = mock_dai.balance_of(owner)
+                + mock_dai.sub_balance_of(owner, 1)
+                + mock_dai.sub_balance_of(owner, 2);
+            assert_eq!(total, mock_dai.total_supply());
+            assert_eq!(mock_dai.total_in_subs(), 250);
+        }
+
+        /// The first deposit mints 1:1 whatever the supply outside the vault, later ones fewer shares
+        /// once the assets grew
+        #[ink::test]
+        fn vault_shares_follow_the_assets() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            assert_eq!(mock_dai.deposit(1_000), Ok(1_000));
+            assert_eq!(mock_dai.balance_of(owner), 1_001_000);
+            assert_eq!(mock_dai.total_vault_shares(), 1_000);
+
+            mock_dai.accrue_assets(1_000).unwrap();
+            assert_eq!(mock_dai.convert_to_assets(500), 1_000);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.deposit(1_000), Err(Error::NotMinter));
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.set_minter(bob, true).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.deposit(1_000), Ok(500));
+            assert_eq!(mock_dai.total_supply(), 1_001_500);
+            assert_eq!(mock_dai.total_assets(), 3_000);
+
+            // shares that came from anywhere but the vault don't redeem
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.transfer(bob, 5_000).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.redeem(501), Err(Error::InsufficientVaultShares));
+            assert_eq!(mock_dai.redeem(500), Ok(1_000));
+            assert_eq!(mock_dai.balance_of(bob), 5_000);
+            assert_eq!(mock_dai.vault_shares_of(bob), 0);
+            assert_eq!(mock_dai.total_assets(), 2_000);
+        }
+
+        /// Vault deposits mint like any other mint, the treasury's cut included
+        #[ink::test]
+        fn vault_deposits_pay_the_treasury_cut() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let treasury = account(5);
+            mock_dai.set_treasury(Some(treasury)).unwrap();
+            mock_dai.set_treasury_mint_bps(1_000).unwrap();
+
+            assert_eq!(mock_dai.deposit(1_000), Ok(1_000));
+            assert_eq!(mock_dai.balance_of(owner), 2_000);
+            assert_eq!(mock_dai.balance_of(treasury), 100);
+            assert_eq!(mock_dai.vault_shares_of(owner), 1_000);
+            assert_eq!(mock_dai.total_vault_shares(), 1_000);
+        }
+
+        /// `mul_div` widens to 256 bits, so only a quotient past `Balance` or a zero divisor fails
+        #[ink::test]
+        fn mul_div_keeps_full_precision_at_the_boundaries() {
+            const MAX: Balance = Balance::MAX;
+            // products far past 128 bits divide back down exactly
+            assert_eq!(mul_div(MAX, MAX, MAX), Ok(MAX));
+            assert_eq!(mul_div(MAX, MAX - 1, MAX), Ok(MAX - 1));
+            assert_eq!(mul_div_rem(MAX - 1, MAX - 2, MAX), Some((MAX - 3, 2)));
+            assert_eq!(
+                mul_div_rem(10u128.pow(30), 10u128.pow(30), 7 * 10u128.pow(24)),
+                Some((
+                    142_857_142_857_142_857_142_857_142_857_142_857,
+                    10u128.pow(24)
+                ))
+            );
+            // only a quotient past `Balance` or a zero divisor fails
+            assert_eq!(mul_div(MAX, 2, 1), Err(Error::Overflow));
+            assert_eq!(mul_div(1, 1, 0), Err(Error::Overflow));
+
+            // the fee portions round either way on top of it
+            assert_eq!(
+                portion(MAX, 9_999, BPS_DENOMINATOR),
+                MAX / 10_000 * 9_999 + MAX % 10_000 * 9_999 / 10_000
+            );
+            assert_eq!(portion(10, 1, 3), 3);
+            assert_eq!(portion_ceil(10, 1, 3), 4);
+            assert_eq!(portion_ceil(9, 1, 3), 3);
+            assert_eq!(portion_ceil(10, 0, 0), 0);
+        }
+
+        /// Transfers and approvals pause independently of each other
+        #[ink::test]
+        fn transfers_and_approvals_pause_separately() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            mock_dai.approve(bob, 100).unwrap();
+
+            // frozen transfers still let the holder revoke
+            mock_dai.set_transfers_paused(true).unwrap();
+            assert_eq!(mock_dai.transfer(bob, 1), Err(Error::Paused));
+            mock_dai.approve(bob, 0).unwrap();
+            assert_eq!(mock_dai.allowance(mock_dai.owner, bob), 0);
+            mock_dai.set_transfers_paused(false).unwrap();
+
+            // and frozen approvals still let tokens move
+            mock_dai.set_approvals_paused(true).unwrap();
+            assert!(mock_dai.approvals_paused());
+            assert_eq!(mock_dai.approve(bob, 100), Err(Error::ApprovalsPaused));
+            mock_dai.transfer(bob, 10).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 10);
+        }
+
+        /// A schedule pays one installment per interval from its allowance, then ends at its count
+        #[ink::test]
+        fn subscription_schedules_stop_at_their_count() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let merchant = account(2);
+            mock_dai.approve(merchant, 1_000).unwrap();
+            let id = mock_dai.create_subscription(merchant, 100, 5, 2).unwrap();
+
+            // anyone may poke it, the first installment is due straight away
+            ink::env::test::set_caller::<Environment>(account(3));
+            mock_dai.execute_subscription(id).unwrap();
+            assert_eq!(mock_dai.balance_of(merchant), 100);
+            assert_eq!(mock_dai.allowance(owner, merchant), 900);
+            assert_eq!(mock_dai.execute_subscription(id), Err(Error::NotDue));
+
+            for _ in 0..5 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            mock_dai.execute_subscription(id).unwrap();
+            assert_eq!(mock_dai.balance_of(merchant), 200);
+            assert_eq!(
+                mock_dai.subscription(id),
+                Some((owner, merchant, 100, 5, 0, 2, 2))
+            );
+
+            for _ in 0..5 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(
+                mock_dai.execute_subscription(id),
+                Err(Error::SubscriptionEnded)
+            );
+            assert_eq!(
+                mock_dai.execute_subscription(id + 1),
+                Err(Error::SubscriptionNotFound)
+            );
+        }
+
+        /// Batch entries run in order, a repeated recipient receives the sum
+        #[ink::test]
+        fn batch_transfer_accumulates_repeated_recipients() {
+            let mut mock_dai = MockDai::new(100);
+            let bob = account(2);
+            mock_dai.batch_transfer(vec![(bob, 30), (bob, 20)]).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 50);
+            assert_eq!(mock_dai.balance_of(mock_dai.owner), 50);
+        }
+
+        /// A later batch entry sees the balance the earlier ones left behind
+        #[ink::test]
+        fn batch_transfer_entries_see_earlier_effects() {
+            let mut mock_dai = MockDai::new(100);
+            let bob = account(2);
+            let carol = account(3);
+
+            // either entry alone fits the balance, the second one fails only after the first
+            assert_eq!(
+                mock_dai.batch_transfer(vec![(bob, 60), (carol, 60)]),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(mock_dai.balance_of(carol), 0);
+        }
+
+        /// A simulated `transfer_from` fails the way the real one would, without touching state
+        #[ink::test]
+        fn simulate_transfer_from_matches_the_real_call() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let spender = account(2);
+            let bob = account(3);
+            mock_dai.approve(spender, 500).unwrap();
+            mock_dai.set_daily_limit(300, 100).unwrap();
+
+            ink::env::test::set_caller::<Environment>(spender);
+            assert_eq!(
+                mock_dai.simulate_transfer_from(owner, bob, 600),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(
+                mock_dai.transfer_from(owner, bob, 600),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(
+                mock_dai.simulate_transfer_from(owner, bob, 400),
+                Err(Error::DailyLimitExceeded)
+            );
+            assert_eq!(
+                mock_dai.transfer_from(owner, bob, 400),
+                Err(Error::DailyLimitExceeded)
+            );
+            assert_eq!(mock_dai.simulate_transfer_from(owner, bob, 200), Ok(()));
+            assert_eq!(mock_dai.sent_in_window(owner), 0);
+            assert_eq!(mock_dai.allowance(owner, spender), 500);
+            assert_eq!(mock_dai.balance_of(bob), 0);
 
-        /// We test if the default constructor does its job.
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.pause().unwrap();
+            ink::env::test::set_caller::<Environment>(spender);
+            assert_eq!(
+                mock_dai.simulate_transfer_from(owner, bob, 200),
+                Err(Error::Paused)
+            );
+        }
+
+        /// A capped spender can't be approved past its cap, other spenders can
         #[ink::test]
-        fn constructor_works() {
-            let mock_dai = MockDai::new(1_000_000);
-            assert_eq!(mock_dai.total_supply(), 1_000_000);
+        fn spender_caps_only_limit_their_spender() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let risky = account(2);
+            let bob = account(3);
+            mock_dai.set_spender_cap(risky, 100).unwrap();
+            assert_eq!(mock_dai.spender_cap(risky), 100);
+
+            mock_dai.approve(risky, 100).unwrap();
+            assert_eq!(
+                mock_dai.increase_allowance(risky, 1),
+                Err(Error::AllowanceTooHigh)
+            );
+            assert_eq!(mock_dai.approve(risky, 101), Err(Error::AllowanceTooHigh));
+            mock_dai.approve(bob, 1_000).unwrap();
+            assert_eq!(
+                mock_dai.migrate_allowance(bob, risky),
+                Err(Error::AllowanceTooHigh)
+            );
+
+            mock_dai.set_spender_cap(risky, Balance::MAX).unwrap();
+            mock_dai.approve(risky, 1_000).unwrap();
         }
 
-        /// We write a test to simulate transfers and then return balances of Bob and Alice
+        /// A legacy balance reads through until its first write moves it into the current layout
         #[ink::test]
-        fn balance_of_returns_correct_values() {
-            // deploy an instance of MockDai token
+        fn legacy_balances_migrate_on_first_touch() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            let alice = account(3);
+            let carol = account(4);
+            // entries left behind by the pre-upgrade code, which counted them as held
+            mock_dai.legacy_balances.insert(bob, &500);
+            mock_dai.legacy_balances.insert(carol, &50);
+            mock_dai.total_supply += 550;
+            mock_dai.total_held += 550;
+            mock_dai.holder_count += 2;
+            mock_dai.holder_buckets[0] += 2;
+            let snapshot = mock_dai.snapshot().unwrap();
+
+            assert_eq!(mock_dai.balance_of(bob), 500);
+            assert!(!mock_dai.is_migrated(bob));
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(alice, 100).unwrap();
+            assert!(mock_dai.is_migrated(bob));
+            assert!(!mock_dai.legacy_balances.contains(bob));
+            assert_eq!(mock_dai.balance_of(bob), 400);
+            assert_eq!(mock_dai.balance_of(alice), 100);
+            assert_eq!(mock_dai.balance_at(bob, snapshot), Ok(500));
+            assert_eq!(mock_dai.holder_count(), 4);
+
+            mock_dai.migrate_account(carol).unwrap();
+            assert!(mock_dai.is_migrated(carol));
+            assert_eq!(mock_dai.balance_of(carol), 50);
+            assert_eq!(mock_dai.balance_at(carol, snapshot), Ok(50));
+            assert_eq!(mock_dai.holder_count(), 4);
+            assert_eq!(mock_dai.holder_distribution()[0], (1, 3));
+            assert_eq!(mock_dai.total_held, mock_dai.total_supply());
+        }
+
+        /// New spenders past the cap are rejected until a revoke frees a slot
+        #[ink::test]
+        fn approvals_per_owner_are_capped() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let spenders = [2, 3, 4].map(account);
+            mock_dai.set_max_approvals_per_owner(2).unwrap();
+
+            mock_dai.approve(spenders[0], 10).unwrap();
+            mock_dai.approve(spenders[1], 10).unwrap();
+            assert_eq!(mock_dai.approval_count(owner), 2);
+            assert_eq!(
+                mock_dai.approve(spenders[2], 10),
+                Err(Error::TooManyApprovals)
+            );
+            // existing spenders can still be changed
+            mock_dai.approve(spenders[1], 20).unwrap();
+
+            mock_dai.approve(spenders[0], 0).unwrap();
+            assert_eq!(mock_dai.approval_count(owner), 1);
+            mock_dai.approve(spenders[2], 10).unwrap();
+            assert_eq!(mock_dai.approval_count(owner), 2);
+        }
+
+        /// The claim leaf hashes the account bytes followed by the little-endian amount
+        #[ink::test]
+        fn claim_leaf_matches_the_documented_encoding() {
+            let mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            let amount: Balance = 0x0102_0304;
+
+            let mut preimage = [2; 48];
+            preimage[32..].copy_from_slice(&amount.to_le_bytes());
+            let mut expected = [0; 32];
+            ink::env::hash_bytes::<Blake2x256>(&preimage, &mut expected);
+
+            assert_eq!(mock_dai.claim_leaf(bob, amount), expected);
+            assert_eq!(
+                mock_dai.claim_leaf(bob, amount),
+                mock_dai.claim_leaf(bob, amount)
+            );
+            assert_ne!(mock_dai.claim_leaf(bob, amount + 1), expected);
+        }
+
+        /// Minting stays off until the activation block, which can only be set once
+        #[ink::test]
+        fn minting_waits_for_its_activation_block() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            mock_dai.set_mint_activation_block(3).unwrap();
+            assert_eq!(
+                mock_dai.set_mint_activation_block(1),
+                Err(Error::MintActivationFixed)
+            );
+
+            assert_eq!(mock_dai.mint(bob, 100), Err(Error::MintingNotActive));
+            assert!(!mock_dai.can_mint(mock_dai.owner));
+            for _ in 0..3 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            mock_dai.mint(bob, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 100);
+        }
+
+        /// A standing order refills after each spend, capped by what the owner still holds
+        #[ink::test]
+        fn auto_allowances_refill_on_use() {
+            let mut mock_dai = MockDai::new(250);
+            let owner = account(1);
+            let spender = account(2);
+            mock_dai.approve_auto(spender, 100, 100).unwrap();
+
+            ink::env::test::set_caller::<Environment>(spender);
+            mock_dai.transfer_from(owner, spender, 60).unwrap();
+            assert_eq!(mock_dai.allowance(owner, spender), 100);
+            mock_dai.transfer_from(owner, spender, 100).unwrap();
+            // only 90 is left to refill with
+            assert_eq!(mock_dai.allowance(owner, spender), 90);
+
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.approve(spender, 10).unwrap();
+            assert_eq!(mock_dai.allowance_refill(owner, spender), None);
+            ink::env::test::set_caller::<Environment>(spender);
+            mock_dai.transfer_from(owner, spender, 10).unwrap();
+            assert_eq!(mock_dai.allowance(owner, spender), 0);
+        }
+
+        /// The owner can kill a holder's approval, the spender's next pull fails
+        #[ink::test]
+        fn owner_force_revokes_an_approval() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            let malicious = account(3);
+            mock_dai.transfer(bob, 500).unwrap();
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.approve(malicious, 500).unwrap();
+            assert_eq!(mock_dai.force_revoke(bob, malicious), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Environment>(mock_dai.owner);
+            mock_dai.force_revoke(bob, malicious).unwrap();
+            assert_eq!(mock_dai.allowance(bob, malicious), 0);
+            let revoked = ink::env::test::recorded_events().last().unwrap();
+            assert!(matches!(
+                <Event as scale::Decode>::decode(&mut &revoked.data[..]).unwrap(),
+                Event::Approval(Approval { amount: 0, .. })
+            ));
+
+            ink::env::test::set_caller::<Environment>(malicious);
+            assert_eq!(
+                mock_dai.transfer_from(bob, malicious, 1),
+                Err(Error::InsufficientAllowance)
+            );
+        }
+
+        /// A queued transfer finalizes only after its delay, a cancelled one goes back to the sender
+        #[ink::test]
+        fn queued_transfers_wait_for_their_delay() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.set_transfer_delay(5).unwrap();
+
+            let id = mock_dai.request_transfer(bob, 300).unwrap();
+            assert_eq!(mock_dai.balance_of(owner), 700);
+            assert_eq!(mock_dai.circulating_supply(), 700);
+            assert_eq!(mock_dai.finalize_transfer(id), Err(Error::StillLocked));
+
+            for _ in 0..5 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            mock_dai.finalize_transfer(id).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 300);
+            assert_eq!(mock_dai.balance_of(owner), 700);
+            assert_eq!(
+                mock_dai.finalize_transfer(id),
+                Err(Error::TransferRequestNotFound)
+            );
+
+            let id = mock_dai.request_transfer(bob, 200).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.cancel_transfer(id), Err(Error::NotRequester));
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.cancel_transfer(id).unwrap();
+            assert_eq!(mock_dai.balance_of(owner), 700);
+            assert_eq!(mock_dai.transfer_request(id), None);
+        }
+
+        /// Subscribers register once per account up to the cap, and a subscriber that isn't a
+        /// contract is skipped without blocking transfers
+        #[ink::test]
+        fn balance_subscribers_are_capped() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            ink::env::test::set_caller::<Environment>(bob);
+            for byte in 0..=MAX_BALANCE_SUBSCRIBERS as u8 {
+                mock_dai
+                    .approve_subscriber(account(100 + byte), true)
+                    .unwrap();
+            }
+            mock_dai.approve_subscriber(account(99), true).unwrap();
+            for byte in 0..MAX_BALANCE_SUBSCRIBERS as u8 {
+                ink::env::test::set_caller::<Environment>(account(100 + byte));
+                mock_dai.subscribe(bob).unwrap();
+                mock_dai.subscribe(bob).unwrap();
+            }
+            assert_eq!(
+                mock_dai.balance_subscribers(bob).len(),
+                MAX_BALANCE_SUBSCRIBERS
+            );
+
+            ink::env::test::set_caller::<Environment>(account(99));
+            assert_eq!(mock_dai.subscribe(bob), Err(Error::TooManySubscribers));
+            ink::env::test::set_caller::<Environment>(account(100));
+            mock_dai.unsubscribe(bob).unwrap();
+            assert!(!mock_dai.balance_subscribers(bob).contains(&account(100)));
+
+            ink::env::test::set_caller::<Environment>(mock_dai.owner);
+            mock_dai.transfer(bob, 10).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 10);
+        }
+
+        /// Registered mock tokens keep their own ledgers next to token 0
+        #[ink::test]
+        fn token_ids_transfer_independently() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let bob = account(2);
+            let usdc = mock_dai.register_token(String::from("USDC")).unwrap();
+            assert_eq!(mock_dai.token_symbol(usdc), Some(String::from("USDC")));
+            mock_dai.mint_token(usdc, owner, 500).unwrap();
+
+            mock_dai.transfer_token(usdc, bob, 200).unwrap();
+            mock_dai.transfer_token(0, bob, 50).unwrap();
+            assert_eq!(mock_dai.balance_of_token(usdc, bob), 200);
+            assert_eq!(mock_dai.balance_of_token(usdc, owner), 300);
+            assert_eq!(mock_dai.balance_of_token(0, bob), 50);
+            assert_eq!(mock_dai.balance_of(bob), 50);
+            assert_eq!(mock_dai.total_supply_of(usdc), 500);
+            assert_eq!(mock_dai.total_supply_of(0), 1_000);
+
+            assert_eq!(
+                mock_dai.transfer_token(usdc, bob, 301),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(
+                mock_dai.transfer_token(usdc + 1, bob, 1),
+                Err(Error::UnknownToken)
+            );
+        }
+
+        /// `value_of` scales a balance by the mock price
+        #[ink::test]
+        fn value_of_follows_the_price() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            assert_eq!(mock_dai.price(), PRICE_PRECISION);
+            assert_eq!(mock_dai.value_of(owner), 1_000);
+
+            // a depeg to 0.995 USD
+            mock_dai.set_price(995_000_000_000_000_000).unwrap();
+            assert_eq!(mock_dai.value_of(owner), 995);
+            mock_dai.set_price(2 * PRICE_PRECISION).unwrap();
+            assert_eq!(mock_dai.value_of(owner), 2_000);
+        }
+
+        /// A vote-locked account can't send until the lock runs out but still receives
+        #[ink::test]
+        fn vote_locks_block_outgoing_transfers() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.transfer(bob, 100).unwrap();
+            mock_dai.lock_for_vote(bob, 3).unwrap();
+
+            mock_dai.transfer(bob, 100).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.transfer(owner, 50), Err(Error::StillLocked));
+
+            for _ in 0..3 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            mock_dai.transfer(owner, 50).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 150);
+        }
+
+        /// Retrying a minted operation id changes nothing, a new id mints again
+        #[ink::test]
+        fn idempotent_mints_ignore_retries() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            mock_dai.mint_idempotent(7, bob, 100).unwrap();
+            mock_dai.mint_idempotent(7, bob, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 100);
+            assert_eq!(mock_dai.total_supply(), 1_100);
+
+            mock_dai.mint_idempotent(8, bob, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 200);
+        }
+
+        /// A funded cohort sums up to what was minted to it
+        #[ink::test]
+        fn sum_balances_reconciles_a_cohort() {
+            let mut mock_dai = MockDai::new(1_000);
+            let cohort = [1, 2, 3].map(account);
+            mock_dai.transfer(cohort[1], 300).unwrap();
+            mock_dai.mint(cohort[2], 500).unwrap();
+
+            assert_eq!(mock_dai.sum_balances(cohort.to_vec()), Ok(1_500));
+            assert_eq!(
+                mock_dai.sum_balances(cohort.to_vec()),
+                Ok(mock_dai.total_supply())
+            );
+            assert_eq!(mock_dai.sum_balances(vec![cohort[1]; 2]), Ok(600));
+        }
+
+        /// An idle balance decays every block, and the decay is burned once the account is touched
+        #[ink::test]
+        fn demurrage_decays_idle_balances() {
+            let mut mock_dai = MockDai::new(10_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.set_demurrage_bps_per_block(100).unwrap();
+
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(mock_dai.balance_of(owner), 9_000);
+            assert_eq!(mock_dai.total_supply(), 10_000);
+
+            mock_dai.transfer(bob, 1_000).unwrap();
+            assert_eq!(mock_dai.balance_of(owner), 8_000);
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+            assert_eq!(mock_dai.total_supply(), 9_000);
+            assert_eq!(mock_dai.total_held, 9_000);
+
+            // a full decay empties the balance and frees its holder slot on the next touch
+            for _ in 0..100 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(mock_dai.balance_of(bob), 0);
+            mock_dai.transfer(bob, 0).unwrap();
+            assert_eq!(mock_dai.total_supply(), 0);
+            assert_eq!(mock_dai.holder_count(), 0);
+        }
+
+        /// `safe_approve` fires a zero `Approval` before the one setting the amount
+        #[ink::test]
+        fn safe_approve_resets_before_setting() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let spender = account(2);
+            mock_dai.approve(spender, 100).unwrap();
+
+            let events_before = ink::env::test::recorded_events().count();
+            mock_dai.safe_approve(spender, 250).unwrap();
+            let amounts = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| {
+                    match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                        Event::Approval(Approval { amount, .. }) => amount,
+                        _ => panic!("expected only approvals"),
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(amounts, vec![0, 250]);
+            assert_eq!(mock_dai.allowance(owner, spender), 250);
+        }
+
+        /// Draining an account drops the live holder count but not the cumulative one
+        #[ink::test]
+        fn ever_holders_counts_drained_accounts() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let bob = account(2);
+            assert_eq!(mock_dai.ever_holders(), 1);
+
+            mock_dai.transfer(bob, 100).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(owner, 100).unwrap();
+            assert_eq!(mock_dai.holder_count(), 1);
+            assert_eq!(mock_dai.ever_holders(), 2);
+
+            // receiving again isn't a new holder
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.transfer(bob, 100).unwrap();
+            assert_eq!(mock_dai.ever_holders(), 2);
+        }
+
+        /// A recipient classified as an exchange pays its class fee, others the base fee
+        #[ink::test]
+        fn recipient_class_picks_the_fee() {
             let mut mock_dai = MockDai::new(1_000_000);
-            // make some mock accounts as we would with `makeAddr` in foundry equivalent
-            
-            // @note keep in mind that when we make mock addresses like we do below, the address derived from Account::from([1; 32]) is just the same as we do in a foundry test where address(this) is the calling contract during testing. so, to create actual accounts where the msg.sender isn't the deployer contract/address, we just skip making an address from 1.
-            // this contract/deployer/msg.sender = AccountId::from([1; 32]);
-            let bob = AccountId::from([2; 32]);
-            let alice = AccountId::from([3; 32]);
+            let owner = account(1);
+            let exchange = account(2);
+            let bob = account(3);
+            mock_dai.set_transfer_fee(100, vec![(owner, 100)]).unwrap();
+            mock_dai
+                .set_classification(exchange, CLASS_EXCHANGE)
+                .unwrap();
+            mock_dai.set_class_fee(CLASS_EXCHANGE, Some(500)).unwrap();
+            assert_eq!(mock_dai.classification_of(exchange), CLASS_EXCHANGE);
 
-            // check that before the DAI transfer, bob doesn't have any token balance
+            mock_dai.transfer(exchange, 10_000).unwrap();
+            assert_eq!(mock_dai.balance_of(exchange), 9_500);
+            mock_dai.transfer(bob, 10_000).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 9_900);
+
+            assert_eq!(
+                mock_dai.set_class_fee(CLASS_UNCLASSIFIED, Some(500)),
+                Err(Error::InvalidFeeConfig)
+            );
+        }
+
+        /// A deadline-bound transfer goes through up to its deadline block and fails after
+        #[ink::test]
+        fn transfer_by_respects_its_deadline() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            ink::env::test::advance_block::<Environment>();
+            mock_dai.transfer_by(bob, 100, 1).unwrap();
+
+            ink::env::test::advance_block::<Environment>();
+            assert_eq!(mock_dai.transfer_by(bob, 100, 1), Err(Error::Expired));
+            assert_eq!(mock_dai.balance_of(bob), 100);
+        }
+
+        /// `set_allowance` reports the signed change it applied
+        #[ink::test]
+        fn set_allowance_returns_the_delta() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let spender = account(2);
+            assert_eq!(mock_dai.set_allowance(spender, 300), Ok(300));
+            assert_eq!(mock_dai.set_allowance(spender, 120), Ok(-180));
+            assert_eq!(mock_dai.set_allowance(spender, 120), Ok(0));
+            assert_eq!(mock_dai.allowance(owner, spender), 120);
+            assert_eq!(
+                mock_dai.set_allowance(spender, Balance::MAX),
+                Err(Error::Overflow)
+            );
+        }
+
+        /// Suppressed events aren't recorded while balances keep updating
+        #[ink::test]
+        fn suppressed_events_are_skipped() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            mock_dai.set_suppress_events(true).unwrap();
+
+            let events_before = ink::env::test::recorded_events().count();
+            for _ in 0..10 {
+                mock_dai.transfer(bob, 10).unwrap();
+            }
+            assert_eq!(ink::env::test::recorded_events().count(), events_before);
+            assert_eq!(mock_dai.balance_of(bob), 100);
+
+            mock_dai.set_suppress_events(false).unwrap();
+            mock_dai.transfer(bob, 10).unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 1);
+        }
+
+        /// Escrowed and liquid supply add up to the total supply, the liquid part being the balances
+        #[ink::test]
+        fn escrowed_and_liquid_supply_reconcile() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let bob = account(2);
+            mock_dai.set_transfer_delay(5).unwrap();
+            mock_dai.transfer(bob, 300).unwrap();
+
+            mock_dai.stake(100).unwrap();
+            mock_dai.request_transfer(bob, 50).unwrap();
+            let epoch = mock_dai.distribute(20).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.lock(30, 10).unwrap();
+
+            assert_eq!(mock_dai.escrowed_supply(), 200);
+            assert_eq!(mock_dai.liquid_supply(), 800);
+            assert_eq!(
+                mock_dai.escrowed_supply() + mock_dai.liquid_supply(),
+                mock_dai.total_supply()
+            );
+            assert_eq!(
+                mock_dai.sum_balances(vec![owner, bob]),
+                Ok(mock_dai.liquid_supply())
+            );
+
+            // claiming moves bob's share out of escrow and into the balances
+            assert_eq!(mock_dai.claim_dividend(epoch), Ok(6));
+            assert_eq!(mock_dai.escrowed_supply(), 194);
+            assert_eq!(
+                mock_dai.sum_balances(vec![owner, bob]),
+                Ok(mock_dai.liquid_supply())
+            );
+
+            // buckets drifting past the supply saturate the views instead of panicking
+            mock_dai.total_staked = 2_000;
+            assert_eq!(mock_dai.liquid_supply(), 0);
+            assert_eq!(mock_dai.circulating_supply(), 0);
+        }
+
+        /// A constrained allowance only pays out to its recipient
+        #[ink::test]
+        fn constrained_allowances_reject_other_recipients() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let spender = account(2);
+            let shop = account(3);
+            let thief = account(4);
+            mock_dai.approve_to_recipient(spender, shop, 300).unwrap();
+            assert_eq!(mock_dai.allowance_recipient(owner, spender), Some(shop));
+
+            ink::env::test::set_caller::<Environment>(spender);
+            assert_eq!(
+                mock_dai.transfer_from(owner, thief, 100),
+                Err(Error::RecipientNotAllowed)
+            );
+            mock_dai.transfer_from(owner, shop, 100).unwrap();
+            assert_eq!(mock_dai.balance_of(shop), 100);
+            assert_eq!(mock_dai.allowance(owner, spender), 200);
+        }
+
+        /// The batched nonce query matches each owner's own nonce
+        #[ink::test]
+        fn nonces_of_batches_permit_nonces() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            let (first, second) = (permit_signer([7; 32]), permit_signer([8; 32]));
+            for (secret, signer, permits) in [([7; 32], first, 2), ([8; 32], second, 1)] {
+                for _ in 0..permits {
+                    let digest = mock_dai.permit_digest(signer, bob, 10, 100, false);
+                    let signature = sign_permit(secret, digest);
+                    mock_dai.permit(signer, bob, 10, 100, signature).unwrap();
+                }
+            }
+
+            let nonces = mock_dai.nonces_of(vec![first, second, bob]).unwrap();
+            assert_eq!(nonces, vec![2, 1, 0]);
+            assert_eq!(nonces[0], mock_dai.nonces(first));
+            assert_eq!(nonces[1], mock_dai.nonces(second));
+        }
+
+        /// A batch sending more than `max_transfers_per_tx` times from its sender is rejected
+        #[ink::test]
+        fn batches_respect_the_per_sender_transfer_cap() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            mock_dai.set_max_transfers_per_tx(2).unwrap();
+
+            assert_eq!(
+                mock_dai.batch_transfer(vec![(bob, 1); 3]),
+                Err(Error::TooManyTransfers)
+            );
             assert_eq!(mock_dai.balance_of(bob), 0);
+            mock_dai.batch_transfer(vec![(bob, 1); 2]).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 2);
+        }
 
-            // transfer some DAI to bob
-            mock_dai.transfer(bob, 500).unwrap(); // we unwrap to return the actual value of the transfer transaction a.k.a result
+        /// Funded accounts trade among themselves without routing through the owner first
+        #[ink::test]
+        fn funded_accounts_transfer_between_each_other() {
+            let mut mock_dai = MockDai::new(0);
+            fund(&mut mock_dai, account(2), 500);
+            fund(&mut mock_dai, account(3), 200);
 
-            // check that bob recieved 500 DAI
-            assert_eq!(mock_dai.balance_of(bob), 500);
+            ink::env::test::set_caller::<Environment>(account(2));
+            mock_dai.transfer(account(3), 100).unwrap();
+            assert_eq!(mock_dai.balance_of(account(2)), 400);
+            assert_eq!(mock_dai.balance_of(account(3)), 300);
+            assert_eq!(mock_dai.total_supply(), 700);
+        }
 
-            // check that during the transfer to bob, ALice didn't mistakenly get DAI tokens
-            assert_eq!(mock_dai.balance_of(alice), 0);
+        /// `stabilize` closes part of the gap to the target supply from either side
+        #[ink::test]
+        fn stabilizer_nudges_supply_toward_its_target() {
+            let mut mock_dai = MockDai::new(1_000);
+            mock_dai.stabilize().unwrap();
+            assert_eq!(mock_dai.total_supply(), 1_000);
+
+            // a 200% reserve ratio on 4_000 of reserve targets 2_000
+            mock_dai
+                .set_stabilizer(Some((4_000, 20_000, 5_000)))
+                .unwrap();
+            assert_eq!(mock_dai.stabilizer_target(), Some(2_000));
+            mock_dai.stabilize().unwrap();
+            assert_eq!(mock_dai.total_supply(), 1_500);
+            mock_dai.stabilize().unwrap();
+            assert_eq!(mock_dai.total_supply(), 1_750);
+
+            mock_dai
+                .set_stabilizer(Some((1_000, 10_000, 5_000)))
+                .unwrap();
+            mock_dai.stabilize().unwrap();
+            assert_eq!(mock_dai.total_supply(), 1_375);
+            assert_eq!(mock_dai.balance_of(account(1)), 1_375);
+
+            assert_eq!(
+                mock_dai.set_stabilizer(Some((1_000, 0, 5_000))),
+                Err(Error::InvalidPeg)
+            );
         }
 
+        /// `max_pullable` follows the allowance and balance, and drops to zero when sending can't happen
         #[ink::test]
-        fn do_an_approval_check() {
-            // @note if we were only making a read from the contract we can lose the `mut` key like below
-            let mock_dai = MockDai::new(1_000_000);
+        fn max_pullable_reflects_what_transfer_from_could_move() {
+            let mut mock_dai = MockDai::new(1_000);
+            let (owner, spender, bob) = (account(1), account(2), account(3));
+            assert_eq!(mock_dai.max_pullable(owner, spender), 0);
 
-            let bob = AccountId::from([2; 32]);
-            let alice = AccountId::from([3; 32]);
+            mock_dai.approve(spender, 300).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, spender), 300);
+            mock_dai.approve(spender, Balance::MAX).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, spender), 1_000);
+            mock_dai.set_daily_limit(400, 100).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, spender), 400);
+            ink::env::test::set_caller::<Environment>(spender);
+            mock_dai.transfer_from(owner, bob, 400).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, spender), 0);
 
-            // make sure that there is no current allowances from bob to alice
-            assert_eq!(mock_dai.allowance(bob, alice), 0);
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.set_daily_limit(Balance::MAX, 100).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, spender), 600);
+            mock_dai.lock_for_vote(owner, 10).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, spender), 0);
+            mock_dai.lock_for_vote(owner, 0).unwrap();
+            mock_dai.pause().unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, spender), 0);
+        }
+
+        /// `transfer_with_burn` delivers the full amount and burns the extra share from the sender
+        #[ink::test]
+        fn transfer_with_burn_burns_on_top_of_the_amount() {
+            let mut mock_dai = MockDai::new(10_000);
+            let (owner, bob) = (account(1), account(2));
+
+            mock_dai.transfer_with_burn(bob, 1_000, 500).unwrap();
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+            assert_eq!(mock_dai.balance_of(owner), 10_000 - 1_000 - 50);
+            assert_eq!(mock_dai.total_supply(), 10_000 - 50);
+
+            assert_eq!(
+                mock_dai.transfer_with_burn(bob, 1_000, MAX_FEE_BPS + 1),
+                Err(Error::FeeTooHigh)
+            );
+            assert_eq!(
+                mock_dai.transfer_with_burn(bob, 8_900, 100),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+        }
+
+        /// `import_allowances` loads allowances until the deployment is initialized
+        #[ink::test]
+        fn import_allowances_only_before_initialize() {
+            let mut mock_dai = MockDai::new(1_000);
+            let (alice, bob, charlie) = (account(2), account(3), account(4));
+
+            mock_dai
+                .import_allowances(Vec::from([
+                    (alice, bob, 100),
+                    (alice, charlie, Balance::MAX),
+                    (bob, charlie, 7),
+                ]))
+                .unwrap();
+            assert_eq!(mock_dai.allowance(alice, bob), 100);
+            assert_eq!(mock_dai.allowance(alice, charlie), Balance::MAX);
+            assert_eq!(mock_dai.allowance(bob, charlie), 7);
+            assert_eq!(ink::env::test::recorded_events().count(), 1 + 3);
+
+            ink::env::test::set_caller::<Environment>(alice);
+            assert_eq!(
+                mock_dai.import_allowances(Vec::from([(alice, bob, 1)])),
+                Err(Error::NotOwner)
+            );
+            ink::env::test::set_caller::<Environment>(account(1));
+            mock_dai.initialize().unwrap();
+            assert_eq!(
+                mock_dai.import_allowances(Vec::from([(alice, bob, 1)])),
+                Err(Error::AlreadyInitialized)
+            );
+            assert_eq!(mock_dai.allowance(alice, bob), 100);
+        }
+
+        /// Gated withdrawals only pay out to whitelisted accounts while swaps in stay open
+        #[ink::test]
+        fn gated_withdrawals_need_the_whitelist() {
+            let (owner, bob, charlie) = (account(1), account(2), account(3));
+            let contract = account(10);
+            ink::env::test::set_callee::<Environment>(contract);
+            let mut mock_dai = MockDai::new(1_000_000);
+            mock_dai.set_withdraw_gated(true).unwrap();
+            mock_dai.set_withdraw_whitelisted(bob, true).unwrap();
+
+            ink::env::test::set_account_balance::<Environment>(contract, 1_000);
+            for swapper in [bob, charlie] {
+                ink::env::test::set_caller::<Environment>(swapper);
+                ink::env::test::set_value_transferred::<Environment>(100);
+                assert_eq!(mock_dai.swap_native_for_dai(), Ok(100));
+            }
+            ink::env::test::set_value_transferred::<Environment>(0);
+            assert_eq!(
+                mock_dai.swap_dai_for_native(100),
+                Err(Error::NotWhitelisted)
+            );
+            assert_eq!(mock_dai.balance_of(charlie), 100);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.swap_dai_for_native(100), Ok(100));
+            assert_eq!(mock_dai.balance_of(bob), 0);
+
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.set_withdraw_gated(false).unwrap();
+            ink::env::test::set_caller::<Environment>(charlie);
+            assert_eq!(mock_dai.swap_dai_for_native(100), Ok(100));
+        }
+
+        /// `limits` mirrors each configured limit
+        #[ink::test]
+        fn limits_aggregates_the_configuration() {
+            let mut mock_dai = MockDai::new(1_000);
+            mock_dai.set_daily_limit(500, 20).unwrap();
+            mock_dai.set_min_balance(10).unwrap();
+            mock_dai
+                .set_transfer_fee(100, Vec::from([(account(9), 100)]))
+                .unwrap();
+            mock_dai.set_max_holders(50).unwrap();
+            mock_dai.set_transfer_delay(5).unwrap();
+            mock_dai.set_supply_cap(5_000).unwrap();
+            mock_dai.set_transfer_bounds(2, 400).unwrap();
+
+            let limits = mock_dai.limits();
+            assert_eq!(
+                limits,
+                Limits {
+                    cap: 5_000,
+                    max_transfer: 400,
+                    min_transfer: 2,
+                    daily_limit: 500,
+                    limit_window: 20,
+                    min_balance: 10,
+                    transfer_fee_bps: 100,
+                    burn_fee_bps: 0,
+                    max_allowance: mock_dai.max_allowance(),
+                    max_holders: 50,
+                    max_transfers_per_tx: mock_dai.max_transfers_per_tx(),
+                    launch_cooldown: 0,
+                    transfer_delay: 5,
+                }
+            );
+        }
+
+        /// Reentering `approve_and_call` from a callback fails with the guard on and gets through with it off.
+        /// A real reentering contract needs a node to deploy it on, so this sets the flag a callback sees
+        #[ink::test]
+        fn reentrancy_guard_toggles_the_reentrant_path() {
+            let mut mock_dai = MockDai::new(1_000);
+            let spender = account(2);
+            assert!(mock_dai.reentrancy_guard_enabled());
+
+            // as seen from inside a callback
+            mock_dai.entered = true;
+            assert_eq!(
+                mock_dai.approve_and_call(spender, 100, Vec::new()),
+                Err(Error::Reentrant)
+            );
+            assert_eq!(mock_dai.allowance(account(1), spender), 0);
+
+            mock_dai.set_reentrancy_guard_enabled(false).unwrap();
+            assert_eq!(mock_dai.ensure_not_reentered(), Ok(()));
+            mock_dai.set_reentrancy_guard_enabled(true).unwrap();
+            assert_eq!(mock_dai.ensure_not_reentered(), Err(Error::Reentrant));
+            mock_dai.entered = false;
+            assert_eq!(mock_dai.ensure_not_reentered(), Ok(()));
+        }
+
+        /// Bumping the domain version changes the separator and voids permits signed before
+        #[ink::test]
+        fn bump_domain_version_invalidates_signatures() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let bob = account(2);
+            let secret = [7; 32];
+            let signer = permit_signer(secret);
+            assert_eq!(mock_dai.domain_version(), PERMIT_DOMAIN_VERSION);
+
+            let separator = mock_dai.domain_separator();
+            let signature =
+                sign_permit(secret, mock_dai.permit_digest(signer, bob, 500, 100, false));
+            mock_dai.bump_domain_version().unwrap();
+            assert_eq!(mock_dai.domain_version(), PERMIT_DOMAIN_VERSION + 1);
+            assert_ne!(mock_dai.domain_separator(), separator);
+            assert_eq!(
+                mock_dai.permit(signer, bob, 500, 100, signature),
+                Err(Error::InvalidSignature)
+            );
+
+            let signature =
+                sign_permit(secret, mock_dai.permit_digest(signer, bob, 500, 100, false));
+            mock_dai.permit(signer, bob, 500, 100, signature).unwrap();
+            assert_eq!(mock_dai.allowance(signer, bob), 500);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(mock_dai.bump_domain_version(), Err(Error::NotOwner));
+        }
+
+        /// Transfer fees build the reserve, which pays redeemers their share of the supply burned
+        #[ink::test]
+        fn reserve_accrues_fees_and_redeems_pro_rata() {
+            let mut mock_dai = MockDai::new(10_000);
+            let (owner, bob) = (account(1), account(2));
+            mock_dai
+                .set_transfer_fee(MAX_FEE_BPS, Vec::from([(account(9), MAX_FEE_BPS)]))
+                .unwrap();
+            mock_dai.set_fees_to_reserve(true).unwrap();
+
+            mock_dai.transfer(bob, 5_000).unwrap();
+            assert_eq!(mock_dai.reserve(), 500);
+            assert_eq!(mock_dai.balance_of(account(9)), 0);
+            assert_eq!(mock_dai.balance_of(bob), 4_500);
+            assert_eq!(mock_dai.balance_of(owner), 5_000);
+            assert_eq!(mock_dai.liquid_supply(), 9_500);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            assert_eq!(
+                mock_dai.redeem_from_reserve(4_501),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(mock_dai.redeem_from_reserve(2_000), Ok(100));
+            assert_eq!(mock_dai.balance_of(bob), 4_500 - 2_000 + 100);
+            assert_eq!(mock_dai.reserve(), 400);
+            assert_eq!(mock_dai.total_supply(), 8_000);
+        }
+
+        /// The running totals keep adding up to the supply across mixed operations
+        #[ink::test]
+        fn conservation_holds_across_mixed_operations() {
+            let contract = account(10);
+            ink::env::test::set_callee::<Environment>(contract);
+            let mut mock_dai = MockDai::new(10_000);
+            let (owner, bob, carol) = (account(1), account(2), account(3));
+            assert!(mock_dai.check_conservation());
+            mock_dai.redenominate(19, 10, true).unwrap();
+            assert_eq!(mock_dai.total_supply(), 100_000);
+            assert!(mock_dai.check_conservation());
+
+            mock_dai
+                .set_transfer_fee(100, Vec::from([(contract, 50), (carol, 50)]))
+                .unwrap();
+            mock_dai.transfer(bob, 20_000).unwrap();
+            let epoch = mock_dai.distribute(3_000).unwrap();
+            assert!(mock_dai.check_conservation());
+            mock_dai.stake(5_000).unwrap();
+            mock_dai.lock(4_000, 10).unwrap();
+            mock_dai.deposit_to_sub(1, 3_000).unwrap();
+            mock_dai.transfer(zero_account(), 1_000).unwrap();
+            mock_dai.mint(carol, 2_500).unwrap();
+            mock_dai.burn(700).unwrap();
+            mock_dai.set_fees_to_reserve(true).unwrap();
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(carol, 9_000).unwrap();
+            mock_dai.redeem_from_reserve(1_000).unwrap();
+            mock_dai.claim_dividend(epoch).unwrap();
+            ink::env::test::set_caller::<Environment>(owner);
+            mock_dai.claim_dividend(epoch).unwrap();
+            mock_dai.sweep_dividend(epoch).unwrap();
+            assert_eq!(
+                mock_dai.redenominate(20, 10, true),
+                Err(Error::EscrowOutstanding)
+            );
+            assert!(mock_dai.escrowed_supply() > 0);
+            assert!(mock_dai.check_conservation());
+        }
+
+        /// The conservation check flags running totals that drifted apart
+        #[ink::test]
+        fn conservation_check_flags_broken_accounting() {
+            assert!(!broken_accounting().check_conservation());
+        }
+
+        /// Vesting releases nothing before the cliff, a linear share midway and the rest at the end
+        #[ink::test]
+        fn vesting_claims_follow_the_cliff_and_linear_schedule() {
+            let mut mock_dai = MockDai::new(10_000);
+            let (owner, bob) = (account(1), account(2));
+            assert_eq!(
+                mock_dai.create_vesting(bob, 1_000, 10, 101, 100),
+                Err(Error::InvalidSchedule)
+            );
+            mock_dai.create_vesting(bob, 1_000, 10, 20, 100).unwrap();
+            assert_eq!(
+                mock_dai.create_vesting(bob, 1_000, 10, 20, 100),
+                Err(Error::VestingExists)
+            );
+            assert_eq!(mock_dai.balance_of(owner), 9_000);
+            assert!(mock_dai.check_conservation());
+
+            let advance_to = |block: BlockNumber| {
+                while ink::env::block_number::<Environment>() < block {
+                    ink::env::test::advance_block::<Environment>();
+                }
+            };
+            ink::env::test::set_caller::<Environment>(bob);
+            advance_to(29);
+            assert_eq!(mock_dai.vested_amount(bob), 0);
+            assert_eq!(mock_dai.claim_vested(), Err(Error::StillLocked));
+
+            advance_to(60);
+            assert_eq!(mock_dai.vested_amount(bob), 500);
+            assert_eq!(mock_dai.claim_vested(), Ok(500));
+            assert_eq!(mock_dai.claim_vested(), Err(Error::StillLocked));
+            advance_to(85);
+            assert_eq!(mock_dai.claim_vested(), Ok(250));
+            assert_eq!(mock_dai.balance_of(bob), 750);
+
+            advance_to(500);
+            assert_eq!(mock_dai.claim_vested(), Ok(250));
+            assert_eq!(mock_dai.balance_of(bob), 1_000);
+            assert_eq!(mock_dai.vesting_of(bob), None);
+            assert!(mock_dai.check_conservation());
+        }
+
+        /// The rounding remainder of a dividend stays escrowed until the owner sweeps it back
+        #[ink::test]
+        fn dividend_remainders_are_escrowed_and_swept() {
+            let mut mock_dai = MockDai::new(1_000);
+            let (owner, bob, carol) = (account(1), account(2), account(3));
+            mock_dai.transfer(bob, 333).unwrap();
+            mock_dai.transfer(carol, 333).unwrap();
+            let epoch = mock_dai.distribute(100).unwrap();
+            assert!(mock_dai.check_conservation());
+
+            for holder in [owner, bob, carol] {
+                ink::env::test::set_caller::<Environment>(holder);
+                assert_eq!(mock_dai.claim_dividend(epoch), Ok(33));
+            }
+            assert_eq!(mock_dai.dividend_unclaimed(epoch), 1);
+            assert!(mock_dai.check_conservation());
+
+            assert_eq!(mock_dai.sweep_dividend(epoch), Err(Error::NotOwner));
+            ink::env::test::set_caller::<Environment>(owner);
+            assert_eq!(mock_dai.sweep_dividend(epoch), Ok(1));
+            assert_eq!(mock_dai.balance_of(owner), 334 - 100 + 33 + 1);
+            assert_eq!(mock_dai.sweep_dividend(epoch), Err(Error::DividendSwept));
+            assert_eq!(
+                mock_dai.sweep_dividend(epoch + 1),
+                Err(Error::DividendNotFound)
+            );
+            assert_eq!(mock_dai.total_dividends_unclaimed, 0);
+            assert!(mock_dai.check_conservation());
+        }
+
+        /// Redenominating waits until nothing is staked, locked or otherwise escrowed
+        #[ink::test]
+        fn redenominate_rejects_outstanding_escrow() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            mock_dai.stake(1_000).unwrap();
+            mock_dai.lock(2_000, 1).unwrap();
+            assert_eq!(
+                mock_dai.redenominate(21, 1_000, true),
+                Err(Error::EscrowOutstanding)
+            );
+            assert_eq!(mock_dai.total_supply(), 1_000_000);
+
+            mock_dai.unstake(1_000).unwrap();
+            assert_eq!(
+                mock_dai.redenominate(21, 1_000, true),
+                Err(Error::EscrowOutstanding)
+            );
+            ink::env::test::advance_block::<Environment>();
+            mock_dai.release_lock().unwrap();
+            mock_dai.redenominate(21, 1_000, true).unwrap();
+            assert_eq!(mock_dai.total_supply(), 1_000_000_000);
+            assert_eq!(mock_dai.balance_of(account(1)), 1_000_000_000);
+            assert!(mock_dai.check_conservation());
+        }
+
+        /// A balance redenominated or grown by reflection leaves the bucket it was last written in
+        #[ink::test]
+        fn holder_buckets_follow_written_balances_after_rescaling() {
+            let counts = |mock_dai: &MockDai| -> Vec<u32> {
+                mock_dai
+                    .holder_distribution()
+                    .iter()
+                    .map(|(_, count)| *count)
+                    .take(4)
+                    .collect()
+            };
+            let mut mock_dai = MockDai::new(1_000_000);
+            let (bob, alice, carol) = (account(2), account(3), account(4));
+            mock_dai.redenominate(21, 1_000, true).unwrap();
+            mock_dai.transfer(bob, 1).unwrap();
+            assert_eq!(counts(&mock_dai), vec![1, 0, 1, 0]);
+
+            // bob's 999 reflect up past 1_000 before he writes again
+            mock_dai.transfer(bob, 998).unwrap();
+            mock_dai
+                .set_transfer_fee(MAX_FEE_BPS, vec![(account(9), MAX_FEE_BPS)])
+                .unwrap();
+            mock_dai.set_reflect_fees(true).unwrap();
+            mock_dai.transfer(carol, 500_000_000).unwrap();
+            assert!(mock_dai.balance_of(bob) >= 1_000);
+            assert_eq!(counts(&mock_dai), vec![1, 0, 2, 0]);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.transfer(alice, 1).unwrap();
+            assert_eq!(counts(&mock_dai), vec![1, 1, 2, 0]);
+            assert_eq!(mock_dai.holder_count(), 4);
+        }
+
+        /// Only subscribers the watched account approved get in, and it can evict them again
+        #[ink::test]
+        fn balance_subscribers_need_the_accounts_consent() {
+            let mut mock_dai = MockDai::new(1_000);
+            let (bob, watcher, squatter) = (account(2), account(20), account(21));
+
+            ink::env::test::set_caller::<Environment>(squatter);
+            assert_eq!(mock_dai.subscribe(bob), Err(Error::SubscriberNotApproved));
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.approve_subscriber(watcher, true).unwrap();
+            assert!(mock_dai.is_subscriber_approved(bob, watcher));
+            ink::env::test::set_caller::<Environment>(watcher);
+            mock_dai.subscribe(bob).unwrap();
+            assert_eq!(mock_dai.balance_subscribers(bob), vec![watcher]);
+
+            ink::env::test::set_caller::<Environment>(bob);
+            mock_dai.approve_subscriber(watcher, false).unwrap();
+            assert!(mock_dai.balance_subscribers(bob).is_empty());
+            ink::env::test::set_caller::<Environment>(watcher);
+            assert_eq!(mock_dai.subscribe(bob), Err(Error::SubscriberNotApproved));
+        }
+
+        /// Exposure history spanning many blocks is found by the binary search at every block
+        #[ink::test]
+        fn approval_exposure_searches_long_histories() {
+            let mut mock_dai = MockDai::new(1_000_000);
+            let owner = account(1);
+            let bob = account(2);
+            for step in 1..=20 {
+                mock_dai.approve(bob, step * 10).unwrap();
+                ink::env::test::advance_block::<Environment>();
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(mock_dai.exposure_checkpoint_count.get(owner), Some(20));
+
+            assert_eq!(mock_dai.approval_exposure_at(owner, 0), 0);
+            for step in 1..20u32 {
+                let expected = Balance::from(step) * 10;
+                assert_eq!(mock_dai.approval_exposure_at(owner, 2 * step - 1), expected);
+                assert_eq!(mock_dai.approval_exposure_at(owner, 2 * step), expected);
+            }
+            assert_eq!(mock_dai.approval_exposure_at(owner, 40), 200);
+        }
+
+        /// Paused approvals block every message that grants more allowance, shrinking one still works
+        #[ink::test]
+        fn approvals_pause_blocks_every_grant() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let (bob, carol) = (account(2), account(3));
+            mock_dai.approve(bob, 100).unwrap();
+            mock_dai.set_approvals_paused(true).unwrap();
+
+            let paused = Err(Error::ApprovalsPaused);
+            assert_eq!(mock_dai.approve(carol, 1), paused);
+            assert_eq!(mock_dai.increase_allowance(bob, 1), paused);
+            assert_eq!(mock_dai.approve_auto(carol, 1, 1), paused);
+            assert_eq!(mock_dai.approve_n_uses(carol, 1, 1), paused);
+            assert_eq!(mock_dai.migrate_allowance(bob, carol), paused);
+            assert_eq!(mock_dai.permit(owner, carol, 1, u64::MAX, [0; 65]), paused);
+            assert_eq!(
+                mock_dai.permit_single_use(owner, carol, 1, u64::MAX, [0; 65]),
+                paused
+            );
+
+            mock_dai.decrease_allowance(bob, 40).unwrap();
+            mock_dai.approve(bob, 50).unwrap();
+            assert_eq!(mock_dai.allowance(owner, bob), 50);
+            assert_eq!(mock_dai.allowance(owner, carol), 0);
+        }
+
+        /// Address, per-account and fee tier setters report the change with their own events
+        #[ink::test]
+        fn address_and_account_setters_fire_events() {
+            let mut mock_dai = MockDai::new(1_000);
+            let bob = account(2);
+            let carol = account(3);
+            mock_dai.set_treasury(Some(bob)).unwrap();
+            mock_dai.set_treasury(Some(carol)).unwrap();
+            mock_dai.set_policy(Some(bob)).unwrap();
+            mock_dai.set_bridge(Some(carol)).unwrap();
+            mock_dai.set_minter(bob, true).unwrap();
+            mock_dai.set_fee_exempt(bob, true).unwrap();
+            mock_dai.set_spender_cap(bob, 500).unwrap();
+            mock_dai.set_classification(bob, CLASS_EXCHANGE).unwrap();
+            mock_dai.set_fee_tiers(Vec::from([(100, 10)])).unwrap();
+
+            let (mut addresses, mut settings, mut tiers) = (Vec::new(), Vec::new(), Vec::new());
+            for event in ink::env::test::recorded_events() {
+                match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                    Event::AddressChanged(AddressChanged {
+                        key,
+                        old_address,
+                        new_address,
+                    }) => addresses.push((key, old_address, new_address)),
+                    Event::AccountSettingChanged(AccountSettingChanged {
+                        key,
+                        account,
+                        old_value,
+                        new_value,
+                    }) => settings.push((key, account, old_value, new_value)),
+                    Event::FeeTiersChanged(FeeTiersChanged { fee_tiers }) => tiers.push(fee_tiers),
+                    _ => {}
+                }
+            }
+            assert_eq!(
+                addresses,
+                vec![
+                    (ConfigKey::Treasury as u8, None, Some(bob)),
+                    (ConfigKey::Treasury as u8, Some(bob), Some(carol)),
+                    (ConfigKey::Policy as u8, None, Some(bob)),
+                    (ConfigKey::Bridge as u8, None, Some(carol)),
+                ]
+            );
+            assert_eq!(
+                settings,
+                vec![
+                    (ConfigKey::Minter as u8, bob, 0, 1),
+                    (ConfigKey::FeeExempt as u8, bob, 0, 1),
+                    (ConfigKey::SpenderCap as u8, bob, Balance::MAX, 500),
+                    (
+                        ConfigKey::Classification as u8,
+                        bob,
+                        CLASS_UNCLASSIFIED.into(),
+                        CLASS_EXCHANGE.into()
+                    ),
+                ]
+            );
+            assert_eq!(tiers, vec![Vec::from([(100, 10)])]);
+        }
+
+        /// The supply cap holds every mint and the transfer bounds every transfer, zero lifting them
+        #[ink::test]
+        fn supply_cap_and_transfer_bounds_are_enforced() {
+            let mut mock_dai = MockDai::new(1_000);
+            let owner = account(1);
+            let bob = account(2);
+            assert_eq!(mock_dai.set_supply_cap(999), Err(Error::SupplyCapExceeded));
+            mock_dai.set_supply_cap(1_100).unwrap();
+            mock_dai.mint(bob, 100).unwrap();
+            assert_eq!(mock_dai.mint(bob, 1), Err(Error::SupplyCapExceeded));
+            mock_dai.set_supply_cap(0).unwrap();
+            mock_dai.mint(bob, 1).unwrap();
+
+            assert_eq!(
+                mock_dai.set_transfer_bounds(50, 10),
+                Err(Error::InvalidTransferBounds)
+            );
+            mock_dai.set_transfer_bounds(10, 50).unwrap();
+            assert_eq!(mock_dai.transfer(bob, 9), Err(Error::TransferTooSmall));
+            assert_eq!(mock_dai.transfer(bob, 51), Err(Error::TransferTooLarge));
+            mock_dai.transfer(bob, 50).unwrap();
+            mock_dai.approve(bob, 500).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, bob), 50);
+
+            mock_dai.set_transfer_bounds(0, 0).unwrap();
+            mock_dai.transfer(bob, 1).unwrap();
+            assert_eq!(mock_dai.max_pullable(owner, bob), 500);
+            assert_eq!(mock_dai.balance_of(bob), 152);
         }
     }
 }
+
+/// Selector table of every message for off-chain tooling
+#[cfg(feature = "std")]
+pub use mock_dai::selector_table;